@@ -0,0 +1,51 @@
+use std::fmt::Write;
+
+// Quotes and escapes `s` as a JSON string literal.
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Joins already encoded JSON values into an array.
+pub fn array(values: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+// Joins key/encoded value pairs into an object, escaping the keys.
+pub fn object<K: AsRef<str>>(fields: impl IntoIterator<Item = (K, String)>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|(k, v)| format!("{}:{}", string(k.as_ref()), v))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        assert_eq!(r#""a\"b\\c\n""#, string("a\"b\\c\n"));
+        assert_eq!(
+            r#"{"ids":[1,2],"name":"x"}"#,
+            object([
+                ("ids", array(["1".to_string(), "2".to_string()])),
+                ("name", string("x")),
+            ])
+        );
+    }
+}
@@ -1,5 +1,8 @@
+pub mod json;
+
 pub struct Config {
     pub file_path: String,
+    pub options: Vec<String>,
 }
 
 impl Config {
@@ -7,11 +10,64 @@ impl Config {
         mut args: impl Iterator<Item = String>,
     ) -> Result<Config, &'static str> {
         args.next();
-            
+
         let file_path = match args.next() {
             Some(arg) => arg,
             None => return Err("Didn't get a file path"),
         };
-        Ok(Config { file_path })
+        let options = args.collect();
+
+        Ok(Config { file_path, options })
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options
+            .iter()
+            .any(|opt| opt.strip_prefix("--") == Some(name))
+    }
+
+    // accepts both `--name value` and `--name=value`
+    pub fn value(&self, name: &str) -> Option<&str> {
+        let mut options = self.options.iter();
+        while let Some(opt) = options.next() {
+            let Some(opt) = opt.strip_prefix("--") else {
+                continue;
+            };
+            if opt == name {
+                return options.next().map(|v| v.as_str());
+            }
+            if let Some((key, value)) = opt.split_once('=') {
+                if key == name {
+                    return Some(value);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(args: &[&str]) -> Config {
+        Config::build(args.iter().map(|s| s.to_string())).unwrap()
+    }
+
+    #[test]
+    fn options() {
+        let config = config(&["aoc", "input.txt", "--json", "--bag", "1 red", "--render=html"]);
+
+        assert_eq!("input.txt", config.file_path);
+        assert!(config.flag("json"));
+        assert!(!config.flag("bag=1"));
+        assert_eq!(Some("1 red"), config.value("bag"));
+        assert_eq!(Some("html"), config.value("render"));
+        assert_eq!(None, config.value("explain"));
+    }
+
+    #[test]
+    fn missing_file_path() {
+        assert!(Config::build(["aoc".to_string()].into_iter()).is_err());
     }
 }
@@ -1,72 +1,232 @@
 use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::str::FromStr;
 
 use aoc::Config;
 
+pub mod likelihood;
+pub mod stats;
+
 pub fn run(config: Config) -> Result<(Option<u32>, Option<u32>), Box<dyn Error>> {
-    let games = fs::read_to_string(config.file_path)?;
+    let games = parse_games(&fs::read_to_string(&config.file_path)?)?;
+    let bag = bag_from(&config)?;
 
-    let part_one = sum_ids(&games);
+    let part_one = sum_ids(&games, &bag);
     let part_two = sum_powers(&games);
 
     Ok((Some(part_one), Some(part_two)))
 }
 
-const AVAILABLE_REDS: u32 = 12;
-const AVAILABLE_GREENS: u32 = 13;
-const AVAILABLE_BLUES: u32 = 14;
+pub fn run_stats(config: &Config) -> Result<String, Box<dyn Error>> {
+    let games = parse_games(&fs::read_to_string(&config.file_path)?)?;
+    let stats = stats::stats(&games, &bag_from(config)?);
 
-pub fn sum_ids(games: &str) -> u32 {
-    games
-        .lines()
-        .map(|g| g.split_once(": ").unwrap())
-        .filter(|(_, bag)| {
-            bag.split("; ")
-                .flat_map(|subset| subset.split(", "))
-                .map(cube_ncolor)
-                .all(|(n, color)| match color {
-                    "red" => n <= AVAILABLE_REDS,
-                    "green" => n <= AVAILABLE_GREENS,
-                    "blue" => n <= AVAILABLE_BLUES,
-                    _ => false,
-                })
+    if config.flag("json") {
+        return Ok(stats.to_json());
+    }
+    Ok(stats.to_string())
+}
+
+fn bag_from(config: &Config) -> Result<Bag, ParseError> {
+    match config.value("bag") {
+        Some(bag) => bag.parse(),
+        None => Ok(Bag::default()),
+    }
+}
+
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid game record: {}", self.0)
+    }
+}
+
+impl Error for ParseError {}
+
+// A set of cubes by color; used both for the bag and for each revealed round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag(BTreeMap<String, u32>);
+
+impl Bag {
+    pub fn new() -> Bag {
+        Bag(BTreeMap::new())
+    }
+
+    pub fn insert(&mut self, color: &str, n: u32) {
+        *self.0.entry(color.to_string()).or_insert(0) += n;
+    }
+
+    pub fn count(&self, color: &str) -> u32 {
+        self.0.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(|c| c.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.0.iter().map(|(c, n)| (c.as_str(), *n))
+    }
+
+    // true when every cube of `other` could have been drawn from this bag
+    pub fn contains(&self, other: &Bag) -> bool {
+        other.iter().all(|(color, n)| n <= self.count(color))
+    }
+
+    fn max_with(&mut self, other: &Bag) {
+        for (color, n) in other.iter() {
+            let count = self.0.entry(color.to_string()).or_insert(0);
+            *count = cmp::max(*count, n);
+        }
+    }
+}
+
+impl Default for Bag {
+    fn default() -> Bag {
+        DEFAULT_BAG.parse().expect("valid default bag")
+    }
+}
+
+impl FromStr for Bag {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Bag, ParseError> {
+        let mut bag = Bag::new();
+        for cube in s.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            let (n, color) = cube_ncolor(cube)?;
+            bag.insert(color, n);
+        }
+        Ok(bag)
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cubes: Vec<String> = self.iter().map(|(c, n)| format!("{n} {c}")).collect();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub rounds: Vec<Bag>,
+}
+
+impl Game {
+    pub fn is_possible_with(&self, bag: &Bag) -> bool {
+        self.rounds.iter().all(|round| bag.contains(round))
+    }
+
+    pub fn minimal_bag(&self) -> Bag {
+        self.rounds.iter().fold(Bag::new(), |mut bag, round| {
+            bag.max_with(round);
+            bag
         })
-        .map(|(game, _)| game.strip_prefix("Game ").unwrap().parse::<u32>().unwrap())
-        .sum()
+    }
 }
 
-fn cube_ncolor(cube: &str) -> (u32, &str) {
-    cube.split_once(" ")
-        .and_then(|(n, color)| Some((n.parse().unwrap(), color)))
-        .unwrap()
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Game, ParseError> {
+        let (game, rounds) = s
+            .split_once(": ")
+            .ok_or_else(|| ParseError(s.to_string()))?;
+        let id = game
+            .strip_prefix("Game ")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or_else(|| ParseError(s.to_string()))?;
+        let rounds = rounds
+            .split(';')
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Game { id, rounds })
+    }
 }
 
-pub fn sum_powers(games: &str) -> u32 {
+pub fn parse_games(games: &str) -> Result<Vec<Game>, ParseError> {
     games
         .lines()
-        .map(|game| {
-            game.rsplit_once(": ")
-                .unwrap()
-                .1
-                .split("; ")
-                .flat_map(|subset| subset.split(", "))
-        })
-        .fold(0, |power, cubes| {
-            let (mut red, mut green, mut blue) = (0, 0, 0);
-
-            for cube in cubes {
-                let (n, color) = cube_ncolor(cube);
-                match color {
-                    "red" => red = cmp::max(n, red),
-                    "green" => green = cmp::max(n, green),
-                    "blue" => blue = cmp::max(n, blue),
-                    _ => {}
-                }
-            }
-
-            power + red * green * blue
+        .filter(|l| !l.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+pub fn possible_games<'a>(games: &'a [Game], bag: &Bag) -> Vec<&'a Game> {
+    games.iter().filter(|g| g.is_possible_with(bag)).collect()
+}
+
+// Every bag containing `lower` but none of `excluded` makes exactly the
+// requested games possible; `lower` itself is the smallest such bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BagBounds {
+    pub lower: Bag,
+    pub excluded: Vec<Bag>,
+}
+
+impl BagBounds {
+    pub fn contains(&self, bag: &Bag) -> bool {
+        bag.contains(&self.lower) && !self.excluded.iter().any(|e| bag.contains(e))
+    }
+}
+
+pub fn bags_for(games: &[Game], ids: &[u32]) -> Option<BagBounds> {
+    let (possible, impossible): (Vec<&Game>, Vec<&Game>) =
+        games.iter().partition(|g| ids.contains(&g.id));
+
+    let lower = possible.iter().fold(Bag::new(), |mut bag, g| {
+        bag.max_with(&g.minimal_bag());
+        bag
+    });
+    let excluded: Vec<Bag> = impossible.iter().map(|g| g.minimal_bag()).collect();
+
+    // growing a bag only makes more games possible, so if the smallest
+    // candidate already allows an excluded game no bag can work
+    if excluded.iter().any(|e| lower.contains(e)) {
+        return None;
+    }
+
+    Some(BagBounds { lower, excluded })
+}
+
+pub fn sum_ids(games: &[Game], bag: &Bag) -> u32 {
+    possible_games(games, bag).iter().map(|g| g.id).sum()
+}
+
+fn cube_ncolor(cube: &str) -> Result<(u32, &str), ParseError> {
+    cube.trim()
+        .split_once(' ')
+        .and_then(|(n, color)| Some((n.parse().ok()?, color.trim())))
+        .ok_or_else(|| ParseError(cube.to_string()))
+}
+
+pub fn sum_powers(games: &[Game]) -> u32 {
+    let colors = all_colors(games);
+
+    games
+        .iter()
+        .map(|g| {
+            let bag = g.minimal_bag();
+            colors.iter().map(|c| bag.count(c)).product::<u32>()
         })
+        .sum()
+}
+
+// a color never drawn in a game still counts, with zero cubes
+fn all_colors(games: &[Game]) -> BTreeSet<&str> {
+    games
+        .iter()
+        .flat_map(|g| g.rounds.iter().flat_map(|r| r.colors()))
+        .collect()
 }
 
 #[cfg(test)]
@@ -83,7 +243,7 @@ Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 Game 6: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 14 blue, 12 red";
 
-        assert_eq!(14, sum_ids(games));
+        assert_eq!(14, sum_ids(&parse_games(games).unwrap(), &Bag::default()));
     }
 
     #[test]
@@ -95,6 +255,69 @@ Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
 
-        assert_eq!(2286, sum_powers(games));
+        assert_eq!(2286, sum_powers(&parse_games(games).unwrap()));
+    }
+
+    #[test]
+    fn custom_bag() {
+        let games = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green, 1 purple
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+        let bag: Bag = "4 red, 3 green, 6 blue, 1 purple".parse().unwrap();
+
+        let games = parse_games(games).unwrap();
+
+        assert_eq!(3, sum_ids(&games, &bag));
+        // game 1 draws a purple cube, which this bag lacks
+        assert_eq!(
+            5,
+            sum_ids(&games, &"20 red, 13 green, 6 blue".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn possible_games_for_bag() {
+        let games = parse_games(
+            "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        )
+        .unwrap();
+
+        let ids: Vec<u32> = possible_games(&games, &Bag::default())
+            .iter()
+            .map(|g| g.id)
+            .collect();
+        assert_eq!(vec![1, 2], ids);
+    }
+
+    #[test]
+    fn bags_for_exact_games() {
+        let games = parse_games(
+            "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        )
+        .unwrap();
+
+        let bounds = bags_for(&games, &[1, 2]).unwrap();
+        assert_eq!(
+            "6 blue, 3 green, 4 red".parse::<Bag>().unwrap(),
+            bounds.lower
+        );
+        assert!(bounds.contains(&Bag::default()));
+        assert!(!bounds.contains(&"20 red, 13 green, 6 blue".parse().unwrap()));
+
+        // any bag allowing game 3 also allows game 1
+        assert_eq!(None, bags_for(&games, &[2, 3]));
+    }
+
+    #[test]
+    fn invalid_bag() {
+        assert!("twelve red".parse::<Bag>().is_err());
+        assert!("Game x: 1 red".parse::<Game>().is_err());
     }
 }
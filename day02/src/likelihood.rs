@@ -0,0 +1,216 @@
+// Likelihood of the recorded rounds assuming each round draws its cubes
+// uniformly at random without replacement, and puts them back afterwards.
+// A round showing k_c cubes of each color c out of a bag holding N_c of
+// them follows the multivariate hypergeometric distribution:
+//
+//     P = prod_c C(N_c, k_c) / C(N, k)    with N = sum N_c, k = sum k_c
+
+use crate::{all_colors, Bag, Game};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    pub num: u128,
+    pub den: u128,
+}
+
+impl Ratio {
+    pub fn new(num: u128, den: u128) -> Ratio {
+        let d = gcd(num, den).max(1);
+        Ratio {
+            num: num / d,
+            den: den / d,
+        }
+    }
+
+    pub fn checked_mul(self, other: Ratio) -> Option<Ratio> {
+        // cross reduce first so products stay as small as possible
+        let a = gcd(self.num, other.den).max(1);
+        let b = gcd(other.num, self.den).max(1);
+        Some(Ratio::new(
+            (self.num / a).checked_mul(other.num / b)?,
+            (self.den / b).checked_mul(other.den / a)?,
+        ))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn checked_choose(n: u32, k: u32) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k) as u128;
+    let n = n as u128;
+    // each partial product C(n - k + i, i) is an integer
+    (1..=k).try_fold(1u128, |c, i| Some(c.checked_mul(n - k + i)? / i))
+}
+
+fn ln_choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+fn round_colors<'a>(bag: &'a Bag, round: &'a Bag) -> impl Iterator<Item = (u32, u32)> + 'a {
+    let missing = round.colors().filter(|c| !bag.colors().any(|b| b == *c));
+    bag.colors()
+        .chain(missing)
+        .map(|c| (bag.count(c), round.count(c)))
+}
+
+fn totals(bag: &Bag, round: &Bag) -> (u32, u32) {
+    round_colors(bag, round).fold((0, 0), |(n, k), (n_c, k_c)| (n + n_c, k + k_c))
+}
+
+// None when the exact value does not fit in 128 bits.
+pub fn round_probability(bag: &Bag, round: &Bag) -> Option<Ratio> {
+    let (n, k) = totals(bag, round);
+    let den = checked_choose(n, k)?;
+    if den == 0 {
+        return Some(Ratio::new(0, 1));
+    }
+    round_colors(bag, round)
+        .map(|(n_c, k_c)| checked_choose(n_c, k_c).map(|c| Ratio::new(c, 1)))
+        .try_fold(Ratio::new(1, den), |p, c| p.checked_mul(c?))
+}
+
+pub fn round_log_likelihood(bag: &Bag, round: &Bag) -> f64 {
+    let (n, k) = totals(bag, round);
+    round_colors(bag, round)
+        .map(|(n_c, k_c)| ln_choose(n_c, k_c))
+        .sum::<f64>()
+        - ln_choose(n, k)
+}
+
+pub fn game_probability(bag: &Bag, game: &Game) -> Option<Ratio> {
+    game.rounds.iter().try_fold(Ratio::new(1, 1), |p, round| {
+        p.checked_mul(round_probability(bag, round)?)
+    })
+}
+
+pub fn game_log_likelihood(bag: &Bag, game: &Game) -> f64 {
+    game.rounds
+        .iter()
+        .map(|round| round_log_likelihood(bag, round))
+        .sum()
+}
+
+// Maximum-likelihood bag holding at most `max_total` cubes. The likelihood
+// often keeps growing with the bag size, so the cap is required. Every bag
+// between the minimal one and the cap is evaluated; ties keep the smallest.
+pub fn estimate_bag(games: &[Game], max_total: u32) -> Option<(Bag, f64)> {
+    let colors: Vec<&str> = all_colors(games).into_iter().collect();
+    let lower = games.iter().fold(Bag::new(), |mut bag, g| {
+        bag.max_with(&g.minimal_bag());
+        bag
+    });
+    let min_total: u32 = lower.iter().map(|(_, n)| n).sum();
+    if min_total > max_total {
+        return None;
+    }
+
+    let mut counts: Vec<u32> = colors.iter().map(|c| lower.count(c)).collect();
+    let mut best: Option<(Vec<u32>, f64)> = None;
+    search(
+        games,
+        &colors,
+        &mut counts,
+        0,
+        max_total - min_total,
+        &mut best,
+    );
+
+    best.map(|(counts, log)| {
+        let mut bag = Bag::new();
+        for (color, n) in colors.iter().zip(counts) {
+            bag.insert(color, n);
+        }
+        (bag, log)
+    })
+}
+
+fn search(
+    games: &[Game],
+    colors: &[&str],
+    counts: &mut Vec<u32>,
+    i: usize,
+    slack: u32,
+    best: &mut Option<(Vec<u32>, f64)>,
+) {
+    if i == colors.len() {
+        let mut bag = Bag::new();
+        for (color, n) in colors.iter().zip(counts.iter()) {
+            bag.insert(color, *n);
+        }
+        let log: f64 = games.iter().map(|g| game_log_likelihood(&bag, g)).sum();
+        if best.as_ref().is_none_or(|(_, b)| log > *b) {
+            *best = Some((counts.clone(), log));
+        }
+        return;
+    }
+
+    let base = counts[i];
+    for extra in 0..=slack {
+        counts[i] = base + extra;
+        search(games, colors, counts, i + 1, slack - extra, best);
+    }
+    counts[i] = base;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_likelihood() {
+        let bag: Bag = "2 red, 2 blue".parse().unwrap();
+        let round: Bag = "1 red, 1 blue".parse().unwrap();
+
+        assert_eq!(Some(Ratio::new(2, 3)), round_probability(&bag, &round));
+        assert!((round_log_likelihood(&bag, &round) - (2.0f64 / 3.0).ln()).abs() < 1e-12);
+
+        let impossible: Bag = "1 green".parse().unwrap();
+        assert_eq!(Some(Ratio::new(0, 1)), round_probability(&bag, &impossible));
+        assert_eq!(f64::NEG_INFINITY, round_log_likelihood(&bag, &impossible));
+    }
+
+    #[test]
+    fn game_likelihood() {
+        let game: Game = "Game 1: 2 red; 1 blue".parse().unwrap();
+        let bag: Bag = "2 red, 1 blue".parse().unwrap();
+
+        assert_eq!(Some(Ratio::new(1, 9)), game_probability(&bag, &game));
+        assert!((game_log_likelihood(&bag, &game) - (1.0f64 / 9.0).ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn large_bag_overflows_exact_only() {
+        let bag: Bag = "1000000 red, 1000000 blue".parse().unwrap();
+        let round: Bag = "20 red, 20 blue".parse().unwrap();
+
+        assert_eq!(None, round_probability(&bag, &round));
+        assert!(round_log_likelihood(&bag, &round).is_finite());
+    }
+
+    #[test]
+    fn maximum_likelihood_bag() {
+        let games = vec!["Game 1: 2 red; 1 blue".parse().unwrap()];
+
+        let (bag, log) = estimate_bag(&games, 5).unwrap();
+        assert_eq!("3 red, 1 blue".parse::<Bag>().unwrap(), bag);
+        assert!((log - (1.0f64 / 8.0).ln()).abs() < 1e-12);
+
+        assert_eq!(None, estimate_bag(&games, 2));
+    }
+}
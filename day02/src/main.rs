@@ -9,6 +9,17 @@ fn main() {
         process::exit(1);
     });
 
+    if config.flag("stats") {
+        match day2::run_stats(&config) {
+            Ok(report) => println!("{report}"),
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     match day2::run(config) {
         Ok((part1, part2)) => println!("part 1: {:?}\npart 2: {:?}", part1, part2),
         Err(e) => {
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use aoc::json;
+
+use crate::{all_colors, Bag, Game};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameSummary {
+    pub id: u32,
+    pub minimal_bag: Bag,
+    pub power: u32,
+    // 0-based index of the first round revealing the maximum of each color
+    pub forced_by: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub bag: Bag,
+    pub games: Vec<GameSummary>,
+    // color -> minimal count -> number of games needing exactly that count
+    pub histograms: BTreeMap<String, BTreeMap<u32, usize>>,
+    pub most_demanding: Option<u32>,
+    // color -> ids of games drawing more cubes of that color than the bag holds
+    pub impossible_by: BTreeMap<String, Vec<u32>>,
+}
+
+pub fn summarize(game: &Game, colors: &[&str]) -> GameSummary {
+    let minimal_bag = game.minimal_bag();
+    let mut forced_by = BTreeMap::new();
+    for (i, round) in game.rounds.iter().enumerate() {
+        for (color, n) in round.iter() {
+            if n == minimal_bag.count(color) {
+                forced_by.entry(color.to_string()).or_insert(i);
+            }
+        }
+    }
+    let power = colors.iter().map(|c| minimal_bag.count(c)).product();
+
+    GameSummary {
+        id: game.id,
+        minimal_bag,
+        power,
+        forced_by,
+    }
+}
+
+pub fn stats(games: &[Game], bag: &Bag) -> Stats {
+    let colors: Vec<&str> = all_colors(games).into_iter().collect();
+    let summaries: Vec<GameSummary> = games.iter().map(|g| summarize(g, &colors)).collect();
+
+    let mut histograms: BTreeMap<String, BTreeMap<u32, usize>> = BTreeMap::new();
+    let mut impossible_by: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for summary in &summaries {
+        for color in &colors {
+            let n = summary.minimal_bag.count(color);
+            *histograms
+                .entry(color.to_string())
+                .or_default()
+                .entry(n)
+                .or_insert(0) += 1;
+
+            if n > bag.count(color) {
+                impossible_by
+                    .entry(color.to_string())
+                    .or_default()
+                    .push(summary.id);
+            }
+        }
+    }
+
+    // ties go to the earliest game
+    let most_demanding = summaries.iter().rev().max_by_key(|s| s.power).map(|s| s.id);
+
+    Stats {
+        bag: bag.clone(),
+        games: summaries,
+        histograms,
+        most_demanding,
+        impossible_by,
+    }
+}
+
+impl Stats {
+    pub fn to_json(&self) -> String {
+        let games = self.games.iter().map(|s| {
+            json::object([
+                ("id", s.id.to_string()),
+                ("minimal_bag", bag_json(&s.minimal_bag)),
+                ("power", s.power.to_string()),
+                (
+                    "forced_by",
+                    json::object(s.forced_by.iter().map(|(c, i)| (c, i.to_string()))),
+                ),
+            ])
+        });
+        let histograms = self.histograms.iter().map(|(color, counts)| {
+            (
+                color,
+                json::object(
+                    counts
+                        .iter()
+                        .map(|(n, games)| (n.to_string(), games.to_string())),
+                ),
+            )
+        });
+        let impossible_by = self
+            .impossible_by
+            .iter()
+            .map(|(color, ids)| (color, json::array(ids.iter().map(|id| id.to_string()))));
+
+        json::object([
+            ("bag", bag_json(&self.bag)),
+            ("games", json::array(games)),
+            ("histograms", json::object(histograms)),
+            (
+                "most_demanding",
+                self.most_demanding
+                    .map_or("null".to_string(), |id| id.to_string()),
+            ),
+            ("impossible_by", json::object(impossible_by)),
+        ])
+    }
+}
+
+fn bag_json(bag: &Bag) -> String {
+    json::object(bag.iter().map(|(c, n)| (c, n.to_string())))
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let colors: Vec<&String> = self.histograms.keys().collect();
+
+        write!(f, "{:>6}", "game")?;
+        for color in &colors {
+            write!(f, " {:>8}", color)?;
+        }
+        writeln!(f, " {:>10}  forced by (round)", "power")?;
+
+        for game in &self.games {
+            write!(f, "{:>6}", game.id)?;
+            for color in &colors {
+                write!(f, " {:>8}", game.minimal_bag.count(color))?;
+            }
+            let forced: Vec<String> = game
+                .forced_by
+                .iter()
+                .map(|(c, i)| format!("{c}@{}", i + 1))
+                .collect();
+            writeln!(f, " {:>10}  {}", game.power, forced.join(" "))?;
+        }
+
+        if let Some(id) = self.most_demanding {
+            writeln!(f, "\nmost demanding: game {id}")?;
+        }
+
+        writeln!(f, "\nimpossible with bag {}:", self.bag)?;
+        for (color, ids) in &self.impossible_by {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            writeln!(f, "  {color}: {}", ids.join(", "))?;
+        }
+
+        write!(f, "\nminimal count histograms (count:games):")?;
+        for (color, counts) in &self.histograms {
+            let counts: Vec<String> = counts.iter().map(|(n, g)| format!("{n}:{g}")).collect();
+            write!(f, "\n  {color}: {}", counts.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_games;
+
+    #[test]
+    fn game_stats() {
+        let games = parse_games(
+            "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        )
+        .unwrap();
+
+        let stats = stats(&games, &Bag::default());
+
+        let first = &stats.games[0];
+        assert_eq!(
+            "6 blue, 2 green, 4 red".parse::<Bag>().unwrap(),
+            first.minimal_bag
+        );
+        assert_eq!(48, first.power);
+        assert_eq!(Some(&1), first.forced_by.get("blue"));
+        assert_eq!(Some(&1), first.forced_by.get("green"));
+        assert_eq!(Some(&0), first.forced_by.get("red"));
+
+        assert_eq!(Some(3), stats.most_demanding);
+        assert_eq!(Some(&vec![3, 4]), stats.impossible_by.get("red"));
+        assert_eq!(Some(&vec![4]), stats.impossible_by.get("blue"));
+        assert_eq!(None, stats.impossible_by.get("green"));
+        assert_eq!(Some(&2), stats.histograms["blue"].get(&6));
+    }
+
+    #[test]
+    fn stats_json() {
+        let games = parse_games("Game 7: 2 red; 1 blue, 3 red").unwrap();
+
+        assert_eq!(
+            r#"{"bag":{"red":2},"games":[{"id":7,"minimal_bag":{"blue":1,"red":3},"power":3,"forced_by":{"blue":1,"red":1}}],"histograms":{"blue":{"1":1},"red":{"3":1}},"most_demanding":7,"impossible_by":{"blue":[7],"red":[7]}}"#,
+            stats(&games, &"2 red".parse().unwrap()).to_json()
+        );
+    }
+}
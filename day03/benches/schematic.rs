@@ -0,0 +1,60 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+const SIZE: usize = 1000;
+
+// Deterministic 1000x1000 schematic mixing short numbers, dots and symbols.
+fn schematic() -> String {
+    let mut seed: u64 = 0x2023_1203;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+
+    let mut s = String::with_capacity(SIZE * (SIZE + 1));
+    for _ in 0..SIZE {
+        let mut col = 0;
+        while col < SIZE {
+            match next() % 10 {
+                0..=2 => {
+                    let len = (1 + next() % 3).min(SIZE - col);
+                    for _ in 0..len {
+                        s.push(char::from(b'0' + (next() % 10) as u8));
+                    }
+                    col += len;
+                    if col < SIZE {
+                        s.push('.');
+                        col += 1;
+                    }
+                    continue;
+                }
+                3 => s.push(['*', '#', '+', '$', '/', '@'][next() % 6]),
+                _ => s.push('.'),
+            }
+            col += 1;
+        }
+        s.push('\n');
+    }
+    s
+}
+
+fn bench<T>(name: &str, f: impl Fn() -> T) {
+    const RUNS: u32 = 10;
+    f();
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        black_box(f());
+    }
+    println!("{name}: {:?}/iter", start.elapsed() / RUNS);
+}
+
+fn main() {
+    let s = schematic();
+    bench("sum_parts 1000x1000", || day3::sum_parts(black_box(&s)));
+    bench("sum_gears_ratio 1000x1000", || {
+        day3::sum_gears_ratio(black_box(&s))
+    });
+}
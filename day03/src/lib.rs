@@ -1,161 +1,346 @@
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 use aoc::Config;
 
-pub fn run(config: Config) -> Result<(Option<u32>, Option<u32>), Box<dyn Error>> {
-    let engine = fs::read_to_string(config.file_path)?;
+pub mod render;
 
-    let part_one = sum_parts(&engine);
-    let part_two = sum_gears_ratio(&engine);
+pub fn run(config: Config) -> Result<(Option<u64>, Option<u64>), Box<dyn Error>> {
+    let engine = fs::read_to_string(&config.file_path)?;
+    let rule = GearRule::build(&config)?;
+
+    let part_one = sum_parts(&engine)?;
+    let part_two = sum_gears(&engine, &rule)?;
 
     Ok((Some(part_one), Some(part_two)))
 }
 
-const DIRECTIONS: [(isize, isize); 8] = [
-    (-1, 0),  // top
-    (-1, -1), // top left
-    (-1, 1),  // top right
-    (1, 0),   // bottom
-    (1, -1),  // bottom left
-    (1, 1),   // bottom right
-    (0, -1),  // left
-    (0, 1),   // right
-];
-
-fn within_boundaries(s: &Vec<&str>, row: isize, col: isize) -> bool {
-    row >= 0 && row <= (s.len() - 1) as isize && col >= 0 && col <= (s[0].len() - 1) as isize
-}
-
-pub fn sum_parts(engine_schematics: &str) -> u32 {
-    let s: Vec<&str> = engine_schematics.lines().collect();
-    engine_schematics
-        .lines()
-        .map(|s| s.chars())
-        .enumerate()
-        .map(|(row, symbols)| {
-            symbols
-                .enumerate()
-                .map(|(col, symbol)| match symbol {
-                    '.' | '0'..='9' => 0,
-                    _ => {
-                        let mut visited = vec![vec![false; s[0].len()]; s.len()];
-                        DIRECTIONS
-                            .iter()
-                            .map(|&(dir_row, dir_col)| {
-                                (dir_row + row as isize, dir_col + col as isize)
-                            })
-                            .filter(|&(row, col)| within_boundaries(&s, row, col))
-                            .filter_map(|(row, col)| {
-                                search_num(&s, row as usize, col as usize, &mut visited)
-                            })
-                            .sum::<u32>()
-                    }
-                })
-                .sum::<u32>()
-        })
-        .sum()
-}
-
-pub fn sum_gears_ratio(engine_schematics: &str) -> u32 {
-    let s: Vec<_> = engine_schematics.lines().collect();
-    engine_schematics
-        .lines()
-        .map(|s| s.chars())
-        .enumerate()
-        .map(|(row, symbols)| {
-            symbols
-                .enumerate()
-                .map(|(col, symbol)| match symbol {
-                    '*' => {
-                        let mut visited = vec![vec![false; s[0].len()]; s.len()];
-                        let part_nums = DIRECTIONS
-                            .iter()
-                            .map(|&(dir_row, dir_col)| {
-                                (dir_row + row as isize, dir_col + col as isize)
-                            })
-                            .filter(|&(row, col)| within_boundaries(&s, row, col))
-                            .filter_map(|(row, col)| {
-                                search_num(&s, row as usize, col as usize, &mut visited)
-                            })
-                            .collect::<Vec<u32>>();
-
-                        if part_nums.len() == 2 {
-                            return part_nums.iter().product();
-                        }
-                        0
-                    }
-                    _ => 0,
-                })
-                .sum::<u32>()
-        })
-        .sum()
+// `--render ansi` or `--render html`, highlighting part numbers and gears
+pub fn run_render(config: &Config) -> Result<String, Box<dyn Error>> {
+    let engine = fs::read_to_string(&config.file_path)?;
+    let rule = GearRule::build(config)?;
+
+    match config.value("render") {
+        Some("ansi") => Ok(render::ansi(&engine, &rule)?),
+        Some("html") => Ok(render::html(&engine, &rule)?),
+        Some(format) => Err(format!("unknown render format: {format}").into()),
+        None => Err("missing render format".into()),
+    }
 }
 
-fn search_num(s: &Vec<&str>, row: usize, col: usize, visited: &mut Vec<Vec<bool>>) -> Option<u32> {
-    if visited[row][col] {
-        return None;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combiner {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Combiner {
+    // None when the result overflows u64
+    pub fn combine(self, mut values: impl Iterator<Item = u64>) -> Option<u64> {
+        match self {
+            Combiner::Product => values.try_fold(1u64, u64::checked_mul),
+            Combiner::Sum => values.try_fold(0u64, u64::checked_add),
+            Combiner::Max => Some(values.max().unwrap_or(0)),
+        }
     }
+}
 
-    visited[row][col] = true;
-    match s[row].chars().nth(col) {
-        Some(char @ '0'..='9') => {
-            let mut num = char.to_digit(10);
-            if col > 0 {
-                num = part_num_left(&s, row, col - 1, visited)
-                    .and_then(|n| Some(n * 10 + num.unwrap()))
-                    .or(num);
-            }
+impl FromStr for Combiner {
+    type Err = String;
 
-            if col < s[0].len() - 1 {
-                num = part_num_right(&s, row, col + 1, num.unwrap(), visited).or(num);
+    fn from_str(s: &str) -> Result<Combiner, String> {
+        match s {
+            "product" => Ok(Combiner::Product),
+            "sum" => Ok(Combiner::Sum),
+            "max" => Ok(Combiner::Max),
+            _ => Err(format!("unknown gear combiner: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowError {
+    // the number starting at this row and column
+    Number { row: usize, col: usize },
+    Parts,
+    // the ratio of the gear at this row and column
+    Ratio { row: usize, col: usize },
+    Total,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OverflowError::Number { row, col } => {
+                write!(f, "number at row {row}, column {col} overflows u64")
+            }
+            OverflowError::Parts => write!(f, "sum of part numbers overflows u64"),
+            OverflowError::Ratio { row, col } => {
+                write!(
+                    f,
+                    "ratio of the gear at row {row}, column {col} overflows u64"
+                )
             }
+            OverflowError::Total => write!(f, "sum of gear ratios overflows u64"),
+        }
+    }
+}
+
+impl Error for OverflowError {}
+
+// Which symbols count as gears, how many part numbers they must touch, and
+// how those part numbers are combined into the gear's ratio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub neighbors: RangeInclusive<usize>,
+    pub combiner: Combiner,
+}
+
+impl Default for GearRule {
+    fn default() -> GearRule {
+        GearRule {
+            symbols: vec!['*'],
+            neighbors: 2..=2,
+            combiner: Combiner::Product,
+        }
+    }
+}
+
+impl GearRule {
+    // reads `--gear-symbols`, `--gear-neighbors` (N or MIN-MAX) and
+    // `--gear-combiner` (product, sum or max), defaulting to the puzzle's rule
+    pub fn build(config: &Config) -> Result<GearRule, Box<dyn Error>> {
+        let mut rule = GearRule::default();
 
-            num
+        if let Some(symbols) = config.value("gear-symbols") {
+            rule.symbols = symbols.chars().collect();
+        }
+        if let Some(neighbors) = config.value("gear-neighbors") {
+            rule.neighbors = match neighbors.split_once('-') {
+                Some((min, max)) => min.trim().parse()?..=max.trim().parse()?,
+                None => {
+                    let n = neighbors.trim().parse()?;
+                    n..=n
+                }
+            };
         }
-        _ => None,
-    }
-}
-
-fn part_num_left(
-    s: &Vec<&str>,
-    row: usize,
-    col: usize,
-    visited: &mut Vec<Vec<bool>>,
-) -> Option<u32> {
-    visited[row][col] = true;
-    match s[row].chars().nth(col) {
-        Some(char @ '0'..='9') => {
-            let num = char.to_digit(10);
-            if col == 0 {
-                return num;
+        if let Some(combiner) = config.value("gear-combiner") {
+            rule.combiner = combiner.parse()?;
+        }
+
+        Ok(rule)
+    }
+
+    pub fn matches(&self, symbol: &Symbol, neighbors: usize) -> bool {
+        self.symbols.contains(&symbol.symbol) && self.neighbors.contains(&neighbors)
+    }
+}
+
+// A run of digits on a single row, spanning columns `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Number {
+    pub value: u64,
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub symbol: char,
+    pub row: usize,
+    pub col: usize,
+}
+
+// Numbers and symbols are referred to by their index in `numbers()` and
+// `symbols()`, both in reading order. Columns count characters rather than
+// bytes, and rows shorter than the widest one read as padded with '.'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // indexes into `symbols` adjacent to each number, and vice versa
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
+    width: usize,
+    height: usize,
+}
+
+impl Schematic {
+    pub fn parse(engine_schematics: &str) -> Result<Schematic, OverflowError> {
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+        // symbol index by position, one row per line; out of range
+        // lookups on short rows behave like '.'
+        let mut grid: Vec<Vec<Option<usize>>> = vec![];
+
+        for (row, line) in engine_schematics.lines().enumerate() {
+            let mut cells = vec![];
+            let mut num: Option<Number> = None;
+
+            for (col, c) in line.chars().enumerate() {
+                if let Some(digit) = c.to_digit(10) {
+                    let n = num.get_or_insert(Number {
+                        value: 0,
+                        row,
+                        start: col,
+                        end: col,
+                    });
+                    n.value = n
+                        .value
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(digit as u64))
+                        .ok_or(OverflowError::Number { row, col: n.start })?;
+                    n.end = col + 1;
+                    cells.push(None);
+                    continue;
+                }
+
+                numbers.extend(num.take());
+                if c == '.' {
+                    cells.push(None);
+                } else {
+                    cells.push(Some(symbols.len()));
+                    symbols.push(Symbol {
+                        symbol: c,
+                        row,
+                        col,
+                    });
+                }
             }
-            part_num_left(&s, row, col - 1, visited)
-                .and_then(|n| Some(n * 10 + num.unwrap()))
-                .or(num)
+
+            numbers.extend(num.take());
+            grid.push(cells);
         }
-        _ => None,
-    }
-}
-
-fn part_num_right(
-    s: &Vec<&str>,
-    row: usize,
-    col: usize,
-    prev: u32,
-    visited: &mut Vec<Vec<bool>>,
-) -> Option<u32> {
-    visited[row][col] = true;
-    match s[row].chars().nth(col) {
-        Some(char @ '0'..='9') => {
-            let n = prev * 10 + char.to_digit(10).unwrap();
-            if col == s[0].len() - 1 {
-                return Some(n);
+
+        let mut number_symbols = vec![vec![]; numbers.len()];
+        let mut symbol_numbers = vec![vec![]; symbols.len()];
+        for (i, n) in numbers.iter().enumerate() {
+            for row in n.row.saturating_sub(1)..=n.row + 1 {
+                for col in n.start.saturating_sub(1)..=n.end {
+                    if let Some(&Some(s)) = grid.get(row).and_then(|cells| cells.get(col)) {
+                        number_symbols[i].push(s);
+                        symbol_numbers[s].push(i);
+                    }
+                }
             }
-            part_num_right(&s, row, col + 1, n, visited).or(Some(n))
         }
-        _ => None,
+
+        Ok(Schematic {
+            numbers,
+            symbols,
+            number_symbols,
+            symbol_numbers,
+            width: grid.iter().map(|cells| cells.len()).max().unwrap_or(0),
+            height: grid.len(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn adjacent_numbers(&self, symbol: usize) -> impl Iterator<Item = &Number> {
+        self.symbol_numbers[symbol]
+            .iter()
+            .map(|&i| &self.numbers[i])
+    }
+
+    pub fn adjacent_symbols(&self, number: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_symbols[number]
+            .iter()
+            .map(|&i| &self.symbols[i])
+    }
+
+    // every symbol along with the part numbers around it
+    pub fn symbol_parts(&self) -> impl Iterator<Item = (&Symbol, Vec<&Number>)> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s, self.adjacent_numbers(i).collect()))
+    }
+
+    // every number along with the symbols around it
+    pub fn number_symbols(&self) -> impl Iterator<Item = (&Number, Vec<&Symbol>)> {
+        self.numbers
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n, self.adjacent_symbols(i).collect()))
+    }
+
+    pub fn parts(&self) -> impl Iterator<Item = &Number> {
+        self.numbers_by_symbol_count(|n| n > 0)
+    }
+
+    pub fn non_parts(&self) -> impl Iterator<Item = &Number> {
+        self.numbers_by_symbol_count(|n| n == 0)
+    }
+
+    // part numbers adjacent to more than one symbol
+    pub fn shared_parts(&self) -> impl Iterator<Item = &Number> {
+        self.numbers_by_symbol_count(|n| n > 1)
     }
+
+    // every gear under `rule` along with its ratio, None if it overflows
+    pub fn gears<'a>(
+        &'a self,
+        rule: &'a GearRule,
+    ) -> impl Iterator<Item = (&'a Symbol, Option<u64>)> {
+        self.symbol_parts()
+            .filter(|(s, numbers)| rule.matches(s, numbers.len()))
+            .map(|(s, numbers)| (s, rule.combiner.combine(numbers.iter().map(|n| n.value))))
+    }
+
+    fn numbers_by_symbol_count(
+        &self,
+        keep: impl Fn(usize) -> bool,
+    ) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(move |(_, symbols)| keep(symbols.len()))
+            .map(|(n, _)| n)
+    }
+}
+
+pub fn sum_parts(engine_schematics: &str) -> Result<u64, OverflowError> {
+    Schematic::parse(engine_schematics)?
+        .parts()
+        .try_fold(0u64, |total, n| total.checked_add(n.value))
+        .ok_or(OverflowError::Parts)
+}
+
+pub fn sum_gears_ratio(engine_schematics: &str) -> Result<u64, OverflowError> {
+    sum_gears(engine_schematics, &GearRule::default())
+}
+
+pub fn sum_gears(engine_schematics: &str, rule: &GearRule) -> Result<u64, OverflowError> {
+    Schematic::parse(engine_schematics)?
+        .gears(rule)
+        .try_fold(0u64, |total, (s, ratio)| {
+            let ratio = ratio.ok_or(OverflowError::Ratio {
+                row: s.row,
+                col: s.col,
+            })?;
+            total.checked_add(ratio).ok_or(OverflowError::Total)
+        })
 }
 
 #[cfg(test)]
@@ -176,7 +361,7 @@ mod tests {
 ...$.*....
 .664.598..";
 
-        assert_eq!(4361, sum_parts(engine_schematics));
+        assert_eq!(Ok(4361), sum_parts(engine_schematics));
     }
 
     #[test]
@@ -193,7 +378,7 @@ mod tests {
 ...$.*....
 .664.598..";
 
-        assert_eq!(467835, sum_gears_ratio(engine_schematics));
+        assert_eq!(Ok(467835), sum_gears_ratio(engine_schematics));
     }
 
     #[test]
@@ -211,6 +396,226 @@ mod tests {
 ...$...*..
 .664...590";
 
-        assert_eq!(722435, sum_gears_ratio(engine_schematics));
+        assert_eq!(Ok(722435), sum_gears_ratio(engine_schematics));
+    }
+
+    #[test]
+    fn part1_number_touching_two_symbols() {
+        let engine_schematics = "\
+.*.
+.5.
+#..";
+
+        assert_eq!(Ok(5), sum_parts(engine_schematics));
+    }
+
+    #[test]
+    fn adjacency() {
+        let schematic = Schematic::parse(
+            "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+        )
+        .unwrap();
+
+        let (gear, parts) = schematic.symbol_parts().next().unwrap();
+        assert_eq!(
+            Symbol {
+                symbol: '*',
+                row: 1,
+                col: 3
+            },
+            *gear
+        );
+        assert_eq!(
+            vec![467, 35],
+            parts.iter().map(|n| n.value).collect::<Vec<_>>()
+        );
+
+        let (number, symbols) = schematic.number_symbols().nth(3).unwrap();
+        assert_eq!(633, number.value);
+        assert_eq!(
+            vec!['#'],
+            symbols.iter().map(|s| s.symbol).collect::<Vec<_>>()
+        );
+
+        let non_parts: Vec<u64> = schematic.non_parts().map(|n| n.value).collect();
+        assert_eq!(vec![114, 58], non_parts);
+        assert_eq!(0, schematic.shared_parts().count());
+    }
+
+    #[test]
+    fn shared_parts() {
+        let schematic = Schematic::parse(
+            "\
+.*..
+.52.
+..#.",
+        )
+        .unwrap();
+
+        let shared: Vec<_> = schematic.shared_parts().collect();
+        assert_eq!(
+            vec![&Number {
+                value: 52,
+                row: 1,
+                start: 1,
+                end: 3
+            }],
+            shared
+        );
+        assert_eq!(2, schematic.adjacent_symbols(0).count());
+        assert_eq!(52, schematic.adjacent_numbers(1).next().unwrap().value);
+    }
+
+    #[test]
+    fn custom_gear_rules() {
+        let engine_schematics = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+        let sum = GearRule {
+            combiner: Combiner::Sum,
+            ..GearRule::default()
+        };
+        assert_eq!(Ok(467 + 35 + 755 + 598), sum_gears(engine_schematics, &sum));
+
+        let lone_max = GearRule {
+            symbols: vec!['*', '#', '+', '$'],
+            neighbors: 1..=1,
+            combiner: Combiner::Max,
+        };
+        assert_eq!(
+            Ok(617 + 633 + 592 + 664),
+            sum_gears(engine_schematics, &lone_max)
+        );
+    }
+
+    #[test]
+    fn overflowing_gear_ratio() {
+        let engine_schematics = "\
+99999.99999
+.....*.....
+99999.99999";
+        let rule = GearRule {
+            neighbors: 1..=6,
+            ..GearRule::default()
+        };
+
+        // 99999^4 does not fit in a u64
+        assert_eq!(
+            Err(OverflowError::Ratio { row: 1, col: 5 }),
+            sum_gears(engine_schematics, &rule)
+        );
+        assert_eq!(None, Combiner::Sum.combine([u64::MAX, 1].into_iter()));
+    }
+
+    #[test]
+    fn overflowing_numbers() {
+        // u64::MAX is 18446744073709551615
+        assert_eq!(Ok(18446744073709551615), sum_parts("18446744073709551615*"));
+        assert_eq!(
+            Err(OverflowError::Number { row: 1, col: 2 }),
+            sum_parts("1*\n..18446744073709551616")
+        );
+        assert_eq!(
+            Err(OverflowError::Parts),
+            sum_parts("18446744073709551615*1")
+        );
+    }
+
+    #[test]
+    fn gear_rule_from_config() {
+        let args = [
+            "day3",
+            "input.txt",
+            "--gear-symbols",
+            "*#",
+            "--gear-neighbors",
+            "1-3",
+            "--gear-combiner=max",
+        ];
+        let config = Config::build(args.iter().map(|s| s.to_string())).unwrap();
+
+        assert_eq!(
+            GearRule {
+                symbols: vec!['*', '#'],
+                neighbors: 1..=3,
+                combiner: Combiner::Max,
+            },
+            GearRule::build(&config).unwrap()
+        );
+
+        let args = ["day3", "input.txt", "--gear-combiner", "min"];
+        let config = Config::build(args.iter().map(|s| s.to_string())).unwrap();
+        assert!(GearRule::build(&config).is_err());
+    }
+
+    #[test]
+    fn multi_byte_symbols() {
+        let engine_schematics = "\
+€..12
+.7.§.
+3....";
+
+        let schematic = Schematic::parse(engine_schematics).unwrap();
+        assert_eq!(5, schematic.width());
+        assert_eq!(
+            vec![
+                Symbol {
+                    symbol: '€',
+                    row: 0,
+                    col: 0
+                },
+                Symbol {
+                    symbol: '§',
+                    row: 1,
+                    col: 3
+                }
+            ],
+            schematic.symbols()
+        );
+        assert_eq!(Ok(12 + 7), sum_parts(engine_schematics));
+        assert_eq!(
+            vec![3],
+            schematic.non_parts().map(|n| n.value).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ragged_rows() {
+        let engine_schematics = "\
+..
+.....*
+..
+42....
+..#
+....5";
+
+        let schematic = Schematic::parse(engine_schematics).unwrap();
+        assert_eq!(6, schematic.width());
+        assert_eq!(6, schematic.height());
+        // 42 only touches the '#' below it; the short rows read as '.'
+        assert_eq!(Ok(42), sum_parts(engine_schematics));
+        assert_eq!(
+            vec![5],
+            schematic.non_parts().map(|n| n.value).collect::<Vec<_>>()
+        );
     }
 }
@@ -1,5 +1,6 @@
 use std::process;
 use std::env;
+use std::fs;
 
 use aoc::Config;
 
@@ -9,6 +10,21 @@ fn main() {
         process::exit(1);
     });
 
+    if config.value("render").is_some() {
+        let rendered = day3::run_render(&config).unwrap_or_else(|e| {
+            eprintln!("Application error: {e}");
+            process::exit(1);
+        });
+        match config.value("out") {
+            Some(path) => fs::write(path, rendered).unwrap_or_else(|e| {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }),
+            None => println!("{rendered}"),
+        }
+        return;
+    }
+
     match day3::run(config) {
         Ok((part1, part2)) => println!("part 1: {:?}\npart 2: {:?}", part1, part2),
         Err(e) => {
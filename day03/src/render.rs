@@ -0,0 +1,184 @@
+use crate::{GearRule, OverflowError, Schematic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Part,
+    NonPart,
+    Gear,
+    Symbol,
+}
+
+impl Cell {
+    fn ansi(self) -> &'static str {
+        match self {
+            Cell::Empty => "",
+            Cell::Part => "\x1b[32m",
+            Cell::NonPart => "\x1b[31m",
+            Cell::Gear => "\x1b[1;33m",
+            Cell::Symbol => "\x1b[36m",
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            Cell::Empty => "",
+            Cell::Part => "part",
+            Cell::NonPart => "non-part",
+            Cell::Gear => "gear",
+            Cell::Symbol => "symbol",
+        }
+    }
+}
+
+// Splits every line into runs of characters sharing the same highlight.
+fn runs(
+    engine_schematics: &str,
+    rule: &GearRule,
+) -> Result<Vec<Vec<(Cell, String)>>, OverflowError> {
+    let schematic = Schematic::parse(engine_schematics)?;
+    // short rows are padded with '.' up to the widest one
+    let lines: Vec<Vec<char>> = engine_schematics
+        .lines()
+        .map(|l| {
+            let mut line: Vec<char> = l.chars().collect();
+            line.resize(schematic.width(), '.');
+            line
+        })
+        .collect();
+    let mut cells: Vec<Vec<Cell>> = lines.iter().map(|l| vec![Cell::Empty; l.len()]).collect();
+
+    for (n, symbols) in schematic.number_symbols() {
+        let cell = if symbols.is_empty() {
+            Cell::NonPart
+        } else {
+            Cell::Part
+        };
+        cells[n.row][n.start..n.end].fill(cell);
+    }
+    for (s, numbers) in schematic.symbol_parts() {
+        cells[s.row][s.col] = if rule.matches(s, numbers.len()) {
+            Cell::Gear
+        } else {
+            Cell::Symbol
+        };
+    }
+
+    Ok(lines
+        .iter()
+        .zip(cells)
+        .map(|(line, cells)| {
+            let mut runs: Vec<(Cell, String)> = vec![];
+            for (&c, cell) in line.iter().zip(cells) {
+                match runs.last_mut() {
+                    Some((last, run)) if *last == cell => run.push(c),
+                    _ => runs.push((cell, c.to_string())),
+                }
+            }
+            runs
+        })
+        .collect())
+}
+
+pub fn ansi(engine_schematics: &str, rule: &GearRule) -> Result<String, OverflowError> {
+    Ok(runs(engine_schematics, rule)?
+        .iter()
+        .map(|runs| {
+            runs.iter()
+                .map(|(cell, run)| match cell {
+                    Cell::Empty => run.clone(),
+                    _ => format!("{}{run}\x1b[0m", cell.ansi()),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+pub fn html(engine_schematics: &str, rule: &GearRule) -> Result<String, OverflowError> {
+    let grid = runs(engine_schematics, rule)?
+        .iter()
+        .map(|runs| {
+            runs.iter()
+                .map(|(cell, run)| match cell {
+                    Cell::Empty => escape(run),
+                    _ => format!("<span class=\"{}\">{}</span>", cell.class(), escape(run)),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Engine schematic</title>
+<style>
+body {{ background: #0f0f23; color: #666; font-family: monospace; }}
+.part {{ color: #00cc00; }}
+.non-part {{ color: #ff5555; }}
+.gear {{ color: #ffff66; font-weight: bold; }}
+.symbol {{ color: #66ccff; }}
+</style>
+</head>
+<body>
+<p><span class="part">part number</span> <span class="non-part">non-part number</span> <span class="gear">gear</span> <span class="symbol">symbol</span></p>
+<pre>
+{grid}
+</pre>
+</body>
+</html>
+"#
+    ))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_ansi() {
+        let engine_schematics = "\
+12.*3
+.....
+7..&.";
+
+        assert_eq!(
+            "\x1b[31m12\x1b[0m.\x1b[1;33m*\x1b[0m\x1b[32m3\x1b[0m\n.....\n\x1b[31m7\x1b[0m..\x1b[36m&\x1b[0m.",
+            ansi(
+                engine_schematics,
+                &GearRule {
+                    neighbors: 1..=1,
+                    ..GearRule::default()
+                }
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn render_html() {
+        let html = html("4<.\n..*", &GearRule::default()).unwrap();
+
+        assert!(html.contains("<pre>\n<span class=\"part\">4</span><span class=\"symbol\">&lt;</span>.\n..<span class=\"symbol\">*</span>\n</pre>"));
+    }
+
+    #[test]
+    fn render_ragged_unicode() {
+        let engine_schematics = "1§\n...2";
+
+        assert_eq!(
+            "\x1b[32m1\x1b[0m\x1b[36m§\x1b[0m..\n...\x1b[31m2\x1b[0m",
+            ansi(engine_schematics, &GearRule::default()).unwrap()
+        );
+    }
+}
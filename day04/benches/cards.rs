@@ -0,0 +1,33 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+const CARDS: usize = 1_000_000;
+
+// A million cards where every third one wins a copy of the two after it.
+fn scratchcards() -> String {
+    (1..=CARDS)
+        .map(|i| match i % 3 {
+            0 => format!("Card {i}: 1 2 9 | 1 2 3\n"),
+            _ => format!("Card {i}: 1 2 3 | 4 5 6\n"),
+        })
+        .collect()
+}
+
+fn bench<T>(name: &str, f: impl Fn() -> T) {
+    const RUNS: u32 = 5;
+    f();
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        black_box(f());
+    }
+    println!("{name}: {:?}/iter", start.elapsed() / RUNS);
+}
+
+fn main() {
+    let s = scratchcards();
+    bench("sum_winning_numbers 1M cards", || {
+        day4::sum_winning_numbers(black_box(&s))
+    });
+    bench("count_cards 1M cards", || day4::count_cards(black_box(&s)));
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use crate::rules::{self, CopyRule, CountError, Scoring};
+use crate::Scratchcard;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardBreakdown {
+    pub id: u32,
+    pub matching: Vec<u32>,
+    pub points: u64,
+    // instances of the card, original included
+    pub copies: u64,
+    // (source card id, copies won from it), in deck order
+    pub sources: Vec<(u32, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown(pub Vec<CardBreakdown>);
+
+pub fn breakdown(
+    cards: &[Scratchcard],
+    scoring: &dyn Scoring,
+    rule: CopyRule,
+) -> Result<Breakdown, CountError> {
+    let copies = rules::copies(cards, rule)?;
+    let mut rows: Vec<CardBreakdown> = cards
+        .iter()
+        .zip(&copies)
+        .map(|(card, &copies)| {
+            let matching = card.matching_numbers();
+            CardBreakdown {
+                id: card.id,
+                points: scoring.points(matching.len()),
+                matching,
+                copies,
+                sources: vec![],
+            }
+        })
+        .collect();
+
+    // every instance of a card wins one copy of each card it points at
+    for (i, card) in cards.iter().enumerate() {
+        for j in rules::won_cards(cards.len(), i, rows[i].matching.len(), rule) {
+            match rows[j].sources.last_mut() {
+                Some((id, n)) if *id == card.id => *n += copies[i],
+                _ => rows[j].sources.push((card.id, copies[i])),
+            }
+        }
+    }
+    for row in &mut rows {
+        row.sources.sort_unstable_by_key(|&(id, _)| id);
+    }
+
+    Ok(Breakdown(rows))
+}
+
+impl Breakdown {
+    // Graphviz digraph of the copies each card hands to the cards it wins.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph copies {\n");
+        for row in &self.0 {
+            dot.push_str(&format!(
+                "    card{0} [label=\"Card {0}\\n{1} points, {2} copies\"];\n",
+                row.id, row.points, row.copies
+            ));
+        }
+        for row in &self.0 {
+            for (source, n) in &row.sources {
+                dot.push_str(&format!(
+                    "    card{source} -> card{} [label=\"{n}\"];\n",
+                    row.id
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>6}  {:<30} {:>8} {:>8}  won from (card:copies)",
+            "card", "matching", "points", "copies"
+        )?;
+        for row in &self.0 {
+            let matching: Vec<String> = row.matching.iter().map(|n| n.to_string()).collect();
+            let sources: Vec<String> = row
+                .sources
+                .iter()
+                .map(|(id, n)| format!("{id}:{n}"))
+                .collect();
+            let line = format!(
+                "{:>6}  {:<30} {:>8} {:>8}  {}",
+                row.id,
+                matching.join(" "),
+                row.points,
+                row.copies,
+                sources.join(" ")
+            );
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+    use crate::rules::Doubling;
+
+    #[test]
+    fn card_breakdown() {
+        let cards = parse_cards(
+            "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        )
+        .unwrap();
+
+        let Breakdown(rows) = breakdown(&cards, &Doubling, CopyRule::Capped).unwrap();
+
+        assert_eq!(
+            CardBreakdown {
+                id: 1,
+                matching: vec![83, 86, 17, 48],
+                points: 8,
+                copies: 1,
+                sources: vec![],
+            },
+            rows[0]
+        );
+        assert_eq!(
+            CardBreakdown {
+                id: 4,
+                matching: vec![84],
+                points: 1,
+                copies: 8,
+                sources: vec![(1, 1), (2, 2), (3, 4)],
+            },
+            rows[3]
+        );
+        assert_eq!(30, rows.iter().map(|r| r.copies).sum::<u64>());
+    }
+
+    #[test]
+    fn dot_export() {
+        let cards = parse_cards(
+            "\
+Card 1: 1 2 | 2 3
+Card 2: 4 | 5",
+        )
+        .unwrap();
+
+        assert_eq!(
+            "\
+digraph copies {
+    card1 [label=\"Card 1\\n1 points, 1 copies\"];
+    card2 [label=\"Card 2\\n0 points, 2 copies\"];
+    card1 -> card2 [label=\"1\"];
+}
+",
+            breakdown(&cards, &Doubling, CopyRule::Next)
+                .unwrap()
+                .to_dot()
+        );
+    }
+}
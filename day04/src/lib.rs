@@ -1,77 +1,132 @@
 use std::collections::HashSet;
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::str::FromStr;
 
 use aoc::Config;
 
-pub fn run(config: Config) -> Result<(Option<u32>, Option<u32>), Box<dyn Error>> {
-    let scratchcards = fs::read_to_string(config.file_path)?;
+pub mod explain;
+pub mod rules;
+pub mod validate;
 
-    let part_one = sum_winning_numbers(&scratchcards);
-    let part_two = count_cards(&scratchcards);
+use rules::{CopyRule, Doubling, Scoring};
+use validate::{Issue, Mode};
 
-    Ok((Some(part_one), Some(part_two)))
+// both parts, along with the issues lenient validation let through
+type Answers = ((Option<u64>, Option<u64>), Vec<Issue>);
+
+pub fn run(config: Config) -> Result<Answers, Box<dyn Error>> {
+    let (cards, scoring, copy_rule, issues) = load(&config)?;
+
+    let part_one = rules::total_points(&cards, scoring.as_ref());
+    let part_two = rules::count_copies(&cards, copy_rule)?;
+
+    Ok(((Some(part_one), Some(part_two)), issues))
 }
 
-pub fn sum_winning_numbers(scratchcards: &str) -> u32 {
-    scratchcards
-        .lines()
-        .map(|card| {
-            winning_numbers(card)
-                .iter()
-                .fold(0, |point, _| match point {
-                    0 => 1,
-                    _ => point * 2,
-                })
-        })
-        .sum()
+// `--explain` prints a per-card table, `--dot` the copy graph instead
+pub fn run_explain(config: &Config) -> Result<(String, Vec<Issue>), Box<dyn Error>> {
+    let (cards, scoring, copy_rule, issues) = load(config)?;
+    let breakdown = explain::breakdown(&cards, scoring.as_ref(), copy_rule)?;
+
+    if config.flag("dot") {
+        return Ok((breakdown.to_dot(), issues));
+    }
+    Ok((breakdown.to_string(), issues))
 }
 
-pub fn count_cards(scratchcards: &str) -> u32 {
-    let cards: Vec<&str> = scratchcards.lines().collect();
-    let mut memo: Vec<Option<u32>> = vec![None; cards.len()];
-    scratchcards
-        .lines()
-        .enumerate()
-        .map(|(i, _)| count_cards_stack(&cards, i, &mut memo))
-        .sum()
+type Setup = (Vec<Scratchcard>, Box<dyn Scoring>, CopyRule, Vec<Issue>);
+
+fn load(config: &Config) -> Result<Setup, Box<dyn Error>> {
+    let cards = parse_cards(&fs::read_to_string(&config.file_path)?)?;
+    let mode = if config.flag("strict") {
+        Mode::Strict
+    } else {
+        Mode::Lenient
+    };
+    let issues = validate::check(&cards, mode)?;
+    let scoring = rules::scoring(config.value("scoring").unwrap_or("doubling"))?;
+    let copy_rule = config.value("copies").unwrap_or("capped").parse()?;
+
+    Ok((cards, scoring, copy_rule, issues))
 }
 
-fn count_cards_stack(cards: &Vec<&str>, start_at: usize, memo: &mut Vec<Option<u32>>) -> u32 {
-    if let Some(n) = memo[start_at] {
-        return n;
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid scratchcard: {}", self.0)
     }
-    let count = 1 + winning_numbers(&cards[start_at])
-        .iter()
-        .enumerate()
-        .map(|(i, _)| count_cards_stack(&cards, start_at + i + 1, memo))
-        .sum::<u32>();
+}
 
-    memo[start_at] = Some(count);
+impl Error for ParseError {}
 
-    count
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scratchcard {
+    pub id: u32,
+    pub winning: Vec<u32>,
+    pub have: Vec<u32>,
 }
 
-fn winning_numbers(s: &str) -> Vec<u32> {
-    let (winning_points, my_points) =
-        points_sets(s.split_once(": ").unwrap().1.split_once(" | ").unwrap());
-    winning_points
-        .intersection(&my_points)
-        .cloned()
-        .collect::<Vec<_>>()
+impl Scratchcard {
+    // numbers you have that are winning, each counted once
+    pub fn matching_numbers(&self) -> Vec<u32> {
+        let winning: HashSet<u32> = self.winning.iter().copied().collect();
+        let mut seen = HashSet::new();
+        self.have
+            .iter()
+            .copied()
+            .filter(|n| winning.contains(n) && seen.insert(*n))
+            .collect()
+    }
+
+    pub fn matches(&self) -> usize {
+        self.matching_numbers().len()
+    }
 }
 
-fn points_sets((winning_points, my_points): (&str, &str)) -> (HashSet<u32>, HashSet<u32>) {
-    (
-        parse_point(winning_points),
-        parse_point(my_points)
-    )
+impl FromStr for Scratchcard {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Scratchcard, ParseError> {
+        let err = || ParseError(s.to_string());
+
+        let (card, numbers) = s.split_once(':').ok_or_else(err)?;
+        let id = card
+            .strip_prefix("Card")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or_else(err)?;
+        let (winning, have) = numbers.split_once('|').ok_or_else(err)?;
+
+        Ok(Scratchcard {
+            id,
+            winning: parse_numbers(winning).ok_or_else(err)?,
+            have: parse_numbers(have).ok_or_else(err)?,
+        })
+    }
+}
+
+fn parse_numbers(s: &str) -> Option<Vec<u32>> {
+    s.split_whitespace().map(|n| n.parse().ok()).collect()
+}
+
+pub fn parse_cards(scratchcards: &str) -> Result<Vec<Scratchcard>, ParseError> {
+    scratchcards
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+pub fn sum_winning_numbers(scratchcards: &str) -> u64 {
+    rules::total_points(&parse_cards(scratchcards).unwrap(), &Doubling)
 }
 
-fn parse_point(s: &str) -> HashSet<u32> {
-    s.split_whitespace()
-        .map(|s| s.parse().unwrap())
-        .collect::<HashSet<u32>>()
+// Total cards once every won copy is scratched, or None if it overflows u64.
+pub fn count_cards(scratchcards: &str) -> Option<u64> {
+    rules::count_copies(&parse_cards(scratchcards).unwrap(), CopyRule::Capped).ok()
 }
 
 #[cfg(test)]
@@ -101,6 +156,48 @@ Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
-        assert_eq!(30, count_cards(scratchcards));
+        assert_eq!(Some(30), count_cards(scratchcards));
+    }
+
+    #[test]
+    fn part2_many_cards() {
+        let scratchcards: String = (1..=10_000)
+            .map(|i| match i % 3 {
+                0 => format!("Card {i}: 1 2 9 | 1 2 3\n"),
+                _ => format!("Card {i}: 1 2 3 | 4 5 6\n"),
+            })
+            .collect();
+
+        // every third card wins one copy of each of the two cards after it
+        assert_eq!(Some(16_665), count_cards(&scratchcards));
+    }
+
+    #[test]
+    fn part2_overflow() {
+        let numbers: Vec<String> = (1..=70).map(|n| n.to_string()).collect();
+        let numbers = numbers.join(" ");
+        let scratchcards: String = (1..=70)
+            .map(|i| format!("Card {i}: {numbers} | {numbers}\n"))
+            .collect();
+
+        // each card doubles the copies of all the cards after it
+        assert_eq!(None, count_cards(&scratchcards));
+    }
+
+    #[test]
+    fn parse_scratchcard() {
+        let card: Scratchcard = "Card  12: 41 48 83 | 83  6 41 83".parse().unwrap();
+
+        assert_eq!(
+            Scratchcard {
+                id: 12,
+                winning: vec![41, 48, 83],
+                have: vec![83, 6, 41, 83],
+            },
+            card
+        );
+        assert_eq!(vec![83, 41], card.matching_numbers());
+        assert!("Card 1: 41 x | 83".parse::<Scratchcard>().is_err());
+        assert!("Card 1: 41 48".parse::<Scratchcard>().is_err());
     }
 }
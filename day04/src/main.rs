@@ -9,8 +9,29 @@ fn main() {
         process::exit(1);
     });
 
+    if config.flag("explain") || config.flag("dot") {
+        match day4::run_explain(&config) {
+            Ok((report, warnings)) => {
+                for warning in warnings {
+                    eprintln!("warning: {warning}");
+                }
+                print!("{report}");
+            }
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     match day4::run(config) {
-        Ok((part1, part2)) => println!("part 1: {:?}\npart 2: {:?}", part1, part2),
+        Ok(((part1, part2), warnings)) => {
+            for warning in warnings {
+                eprintln!("warning: {warning}");
+            }
+            println!("part 1: {:?}\npart 2: {:?}", part1, part2);
+        }
         Err(e) => {
             eprintln!("Application error: {e}");
             process::exit(1);
@@ -0,0 +1,289 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Scratchcard;
+
+// Points earned by a card given how many of its numbers match.
+pub trait Scoring {
+    fn points(&self, matches: usize) -> u64;
+}
+
+impl<F: Fn(usize) -> u64> Scoring for F {
+    fn points(&self, matches: usize) -> u64 {
+        self(matches)
+    }
+}
+
+// 1 point for the first match, doubled for each one after it
+pub struct Doubling;
+
+impl Scoring for Doubling {
+    fn points(&self, matches: usize) -> u64 {
+        match matches {
+            0 => 0,
+            n => 1u64.checked_shl(n as u32 - 1).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+// 1 point per match
+pub struct Linear;
+
+impl Scoring for Linear {
+    fn points(&self, matches: usize) -> u64 {
+        matches as u64
+    }
+}
+
+// the n-th Fibonacci number for n matches: 0, 1, 1, 2, 3, 5, ...
+pub struct Fibonacci;
+
+impl Scoring for Fibonacci {
+    fn points(&self, matches: usize) -> u64 {
+        let (mut a, mut b) = (0u64, 1u64);
+        for _ in 0..matches {
+            (a, b) = (b, a.saturating_add(b));
+        }
+        a
+    }
+}
+
+pub fn scoring(name: &str) -> Result<Box<dyn Scoring>, String> {
+    match name {
+        "doubling" => Ok(Box::new(Doubling)),
+        "linear" => Ok(Box::new(Linear)),
+        "fibonacci" => Ok(Box::new(Fibonacci)),
+        _ => Err(format!("unknown scoring: {name}")),
+    }
+}
+
+pub fn total_points(cards: &[Scratchcard], scoring: &dyn Scoring) -> u64 {
+    cards
+        .iter()
+        .map(|card| scoring.points(card.matches()))
+        .fold(0, u64::saturating_add)
+}
+
+// Which cards a card with N matches wins a copy of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyRule {
+    // the next N cards, which must all exist
+    Next,
+    // the next N cards, ignoring those past the end of the deck
+    Capped,
+    // the next N cards, continuing from the top of the deck
+    WrapAround,
+}
+
+impl FromStr for CopyRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CopyRule, String> {
+        match s {
+            "next" => Ok(CopyRule::Next),
+            "capped" => Ok(CopyRule::Capped),
+            "wrap" => Ok(CopyRule::WrapAround),
+            _ => Err(format!("unknown copy rule: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountError {
+    Overflow,
+    // the card wins copies of cards past the end of the deck
+    PastEnd(u32),
+    // wrap-around makes cards win copies of each other in a loop, so this
+    // card keeps receiving copies forever
+    Endless(u32),
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CountError::Overflow => write!(f, "card count overflows u64"),
+            CountError::PastEnd(id) => write!(f, "card {id} wins copies past the end of the deck"),
+            CountError::Endless(id) => write!(f, "card {id} receives copies endlessly"),
+        }
+    }
+}
+
+impl Error for CountError {}
+
+pub fn count_copies(cards: &[Scratchcard], rule: CopyRule) -> Result<u64, CountError> {
+    copies(cards, rule)?
+        .into_iter()
+        .try_fold(0u64, |total, n| total.checked_add(n))
+        .ok_or(CountError::Overflow)
+}
+
+// Number of instances of each card, original included.
+pub fn copies(cards: &[Scratchcard], rule: CopyRule) -> Result<Vec<u64>, CountError> {
+    let matches: Vec<usize> = cards.iter().map(|c| c.matches()).collect();
+    if rule == CopyRule::WrapAround {
+        return wrapped_copies(cards, &matches);
+    }
+
+    // copies won by earlier cards start at `gained[i]` and stop before
+    // `lost[i]`; kept apart so the running count never goes negative
+    let mut gained = vec![0u64; cards.len() + 1];
+    let mut lost = vec![0u64; cards.len() + 1];
+    let mut won: u64 = 0;
+    let mut copies = Vec::with_capacity(cards.len());
+
+    for (i, &n) in matches.iter().enumerate() {
+        won = won.checked_add(gained[i]).ok_or(CountError::Overflow)? - lost[i];
+        let instances = won.checked_add(1).ok_or(CountError::Overflow)?;
+        copies.push(instances);
+
+        if n == 0 {
+            continue;
+        }
+        if rule == CopyRule::Next && i + n >= cards.len() {
+            return Err(CountError::PastEnd(cards[i].id));
+        }
+        let end = (i + 1 + n).min(cards.len());
+        gained[i + 1] = gained[i + 1]
+            .checked_add(instances)
+            .ok_or(CountError::Overflow)?;
+        lost[end] = lost[end]
+            .checked_add(instances)
+            .ok_or(CountError::Overflow)?;
+    }
+
+    Ok(copies)
+}
+
+// Indexes of the cards won by the card at index `i`, in a deck of `len`.
+pub fn won_cards(
+    len: usize,
+    i: usize,
+    matches: usize,
+    rule: CopyRule,
+) -> impl Iterator<Item = usize> {
+    let wrap = rule == CopyRule::WrapAround;
+    (i + 1..=i + matches)
+        .filter(move |&j| wrap || j < len)
+        .map(move |j| j % len)
+}
+
+// Wrapping copies may point back at earlier cards, so instances are pushed
+// in topological order. Cards left over sit on or downstream of a cycle,
+// and the first one still receiving copies is reported.
+fn wrapped_copies(cards: &[Scratchcard], matches: &[usize]) -> Result<Vec<u64>, CountError> {
+    let len = cards.len();
+    let won = |i: usize| won_cards(len, i, matches[i], CopyRule::WrapAround);
+
+    let mut indegree = vec![0usize; len];
+    for i in 0..len {
+        for j in won(i) {
+            indegree[j] += 1;
+        }
+    }
+
+    let mut copies = vec![1u64; len];
+    let mut ready: Vec<usize> = (0..len).filter(|&i| indegree[i] == 0).rev().collect();
+    let mut done = 0;
+    while let Some(i) = ready.pop() {
+        done += 1;
+        for j in won(i) {
+            copies[j] = copies[j]
+                .checked_add(copies[i])
+                .ok_or(CountError::Overflow)?;
+            indegree[j] -= 1;
+            if indegree[j] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+
+    if done < len {
+        let i = (0..len).find(|&i| indegree[i] > 0).unwrap();
+        return Err(CountError::Endless(cards[i].id));
+    }
+    Ok(copies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    fn cards() -> Vec<Scratchcard> {
+        parse_cards(
+            "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn scoring_strategies() {
+        let cards = cards();
+
+        assert_eq!(13, total_points(&cards, &Doubling));
+        assert_eq!(4 + 2 + 2 + 1, total_points(&cards, &Linear));
+        assert_eq!(3 + 1 + 1 + 1, total_points(&cards, &Fibonacci));
+        assert_eq!(90, total_points(&cards, &|m: usize| (m * 10) as u64));
+        assert!(scoring("squares").is_err());
+    }
+
+    #[test]
+    fn copy_rules() {
+        let cards = cards();
+
+        assert_eq!(Ok(30), count_copies(&cards, CopyRule::Next));
+        assert_eq!(Ok(30), count_copies(&cards, CopyRule::Capped));
+        assert_eq!(Ok(30), count_copies(&cards, CopyRule::WrapAround));
+
+        let cards = parse_cards(
+            "\
+Card 1: 1 | 2
+Card 2: 1 2 | 1 2
+Card 3: 1 | 1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Err(CountError::PastEnd(2)),
+            count_copies(&cards, CopyRule::Next)
+        );
+        assert_eq!(Ok(vec![1, 1, 2]), copies(&cards, CopyRule::Capped));
+        // cards 2 and 3 both wrap onto card 1, which wins nothing
+        assert_eq!(Ok(vec![4, 1, 2]), copies(&cards, CopyRule::WrapAround));
+
+        let cards = parse_cards(
+            "\
+Card 1: 1 | 2
+Card 2: 1 2 | 1 2
+Card 3: 1 2 | 1 2",
+        )
+        .unwrap();
+
+        // cards 2 and 3 win copies of each other, and both feed card 1
+        assert_eq!(
+            Err(CountError::Endless(1)),
+            count_copies(&cards, CopyRule::WrapAround)
+        );
+    }
+
+    #[test]
+    fn wrap_around_without_cycles() {
+        let cards = parse_cards(
+            "\
+Card 1: 1 | 2
+Card 2: 1 | 2
+Card 3: 1 2 | 1 2",
+        )
+        .unwrap();
+
+        assert_eq!(Ok(vec![2, 2, 1]), copies(&cards, CopyRule::WrapAround));
+        assert_eq!("wrap".parse(), Ok(CopyRule::WrapAround));
+    }
+}
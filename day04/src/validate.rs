@@ -0,0 +1,234 @@
+use std::collections::{BTreeSet, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::Scratchcard;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    DuplicateWinning {
+        card: u32,
+        number: u32,
+    },
+    DuplicateHave {
+        card: u32,
+        number: u32,
+    },
+    // no card with any of these ids, though later ones exist
+    MissingIds(RangeInclusive<u32>),
+    // id not greater than the one of the card before it
+    OutOfOrder {
+        card: u32,
+        after: u32,
+    },
+    // number counts differ from the first card's
+    ColumnCount {
+        card: u32,
+        winning: usize,
+        have: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::DuplicateWinning { card, number } => {
+                write!(f, "card {card}: winning number {number} is repeated")
+            }
+            Issue::DuplicateHave { card, number } => {
+                write!(f, "card {card}: number {number} you have is repeated")
+            }
+            Issue::MissingIds(ids) if ids.start() == ids.end() => {
+                write!(f, "card {} is missing", ids.start())
+            }
+            Issue::MissingIds(ids) => {
+                write!(f, "cards {} to {} are missing", ids.start(), ids.end())
+            }
+            Issue::OutOfOrder { card, after } => write!(f, "card {card} comes after card {after}"),
+            Issue::ColumnCount {
+                card,
+                winning,
+                have,
+            } => write!(
+                f,
+                "card {card}: {winning} winning and {have} numbers you have differ from the first card"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // any issue rejects the deck
+    Strict,
+    // issues are reported but the deck is used as is
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError(pub Vec<Issue>);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let issues: Vec<String> = self.0.iter().map(|i| i.to_string()).collect();
+        write!(f, "invalid scratchcards: {}", issues.join("; "))
+    }
+}
+
+impl Error for ValidationError {}
+
+pub fn validate(cards: &[Scratchcard]) -> Vec<Issue> {
+    let mut issues = vec![];
+    let columns = cards.first().map(|c| (c.winning.len(), c.have.len()));
+    let mut prev: Option<u32> = None;
+
+    for card in cards {
+        duplicates(&card.winning, |number| {
+            issues.push(Issue::DuplicateWinning {
+                card: card.id,
+                number,
+            })
+        });
+        duplicates(&card.have, |number| {
+            issues.push(Issue::DuplicateHave {
+                card: card.id,
+                number,
+            })
+        });
+
+        match prev {
+            Some(after) if card.id <= after => issues.push(Issue::OutOfOrder {
+                card: card.id,
+                after,
+            }),
+            _ => prev = Some(card.id),
+        }
+
+        if columns != Some((card.winning.len(), card.have.len())) {
+            issues.push(Issue::ColumnCount {
+                card: card.id,
+                winning: card.winning.len(),
+                have: card.have.len(),
+            });
+        }
+    }
+
+    issues.extend(missing_ids(cards).map(Issue::MissingIds));
+    issues
+}
+
+// Gaps in the ids from 1 up to the highest one, wherever the cards are.
+fn missing_ids(cards: &[Scratchcard]) -> impl Iterator<Item = RangeInclusive<u32>> {
+    let ids: BTreeSet<u32> = cards.iter().map(|c| c.id).collect();
+    let mut expected = Some(1);
+
+    ids.into_iter().filter_map(move |id| {
+        let gap = expected.filter(|&e| e < id).map(|e| e..=id - 1);
+        expected = id.checked_add(1);
+        gap
+    })
+}
+
+// Issues found under `mode`: strict rejects the deck when there is any.
+pub fn check(cards: &[Scratchcard], mode: Mode) -> Result<Vec<Issue>, ValidationError> {
+    let issues = validate(cards);
+    if mode == Mode::Strict && !issues.is_empty() {
+        return Err(ValidationError(issues));
+    }
+    Ok(issues)
+}
+
+// reports each repeated number once
+fn duplicates(numbers: &[u32], mut report: impl FnMut(u32)) {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    for &n in numbers {
+        if !seen.insert(n) && reported.insert(n) {
+            report(n);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cards;
+
+    #[test]
+    fn valid_deck() {
+        let cards = parse_cards(
+            "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        )
+        .unwrap();
+
+        assert_eq!(Ok(vec![]), check(&cards, Mode::Strict));
+    }
+
+    #[test]
+    fn deck_issues() {
+        let cards = parse_cards(
+            "\
+Card 1: 41 48 41 | 83 86 6 86 86
+Card 4: 13 32 20 | 61 30 68 82 17
+Card 3: 1 21 53 | 69 82 63 72
+Card 5: 1 21 53 | 69 82 63 72 1",
+        )
+        .unwrap();
+
+        let issues = vec![
+            Issue::DuplicateWinning {
+                card: 1,
+                number: 41,
+            },
+            Issue::DuplicateHave {
+                card: 1,
+                number: 86,
+            },
+            Issue::OutOfOrder { card: 3, after: 4 },
+            Issue::ColumnCount {
+                card: 3,
+                winning: 3,
+                have: 4,
+            },
+            // card 3 is out of order, but not missing
+            Issue::MissingIds(2..=2),
+        ];
+
+        assert_eq!(issues, validate(&cards));
+        assert_eq!(Ok(issues.clone()), check(&cards, Mode::Lenient));
+        assert_eq!(Err(ValidationError(issues)), check(&cards, Mode::Strict));
+    }
+
+    #[test]
+    fn wide_gaps() {
+        let cards = parse_cards(&format!(
+            "\
+Card 1: 1 | 2
+Card 4000000000: 1 | 2
+Card {}: 1 | 2
+Card 3: 1 | 2",
+            u32::MAX
+        ))
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                Issue::OutOfOrder {
+                    card: 3,
+                    after: u32::MAX
+                },
+                Issue::MissingIds(2..=2),
+                Issue::MissingIds(4..=3_999_999_999),
+                Issue::MissingIds(4_000_000_001..=u32::MAX - 1),
+            ],
+            validate(&cards)
+        );
+        assert_eq!(
+            "cards 4 to 3999999999 are missing",
+            Issue::MissingIds(4..=3_999_999_999).to_string()
+        );
+    }
+}
@@ -1,151 +1,296 @@
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::iter::zip;
 use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 use aoc::Config;
 
+pub mod lint;
+pub mod range_map;
+pub mod trace;
+
+use range_map::RangeMap;
+
 pub fn run(config: Config) -> Result<(Option<u64>, Option<u64>), Box<dyn Error>> {
-    let almanac = fs::read_to_string(config.file_path)?;
+    let almanac: Almanac = fs::read_to_string(config.file_path)?.parse()?;
+    almanac.validate()?;
 
-    let part_one = min_location(&almanac);
-    let part_two = seeds_range_min_location(&almanac);
+    let part_one = almanac.min_location()?;
+    let part_two = almanac.seeds_range_min_location()?;
 
-    Ok((Some(part_one), Some(part_two)))
+    Ok((part_one, part_two))
 }
 
-pub fn min_location(almanac: &str) -> u64 {
-    let mut lines = almanac.lines();
-    let mut src_numbers = initial_seeds(&mut lines);
-    let mut dst_number: Vec<Option<u64>> = vec![None; src_numbers.len()];
-
-    // skips first seed-to-soil map
-    for line in lines.filter(|l| !l.trim_start().is_empty()).skip(1) {
-        if line.ends_with("map:") {
-            src_numbers = collect_values(&src_numbers, dst_number.drain(..));
-            dst_number.resize_with(src_numbers.len(), Default::default);
-            continue;
-        }
+// `--trace` follows every listed seed, then the best seed of part two
+pub fn run_trace(config: &Config) -> Result<String, Box<dyn Error>> {
+    let almanac: Almanac = fs::read_to_string(&config.file_path)?.parse()?;
+    almanac.validate()?;
 
-        let (dst_range, src_range, _) = ranges_from(line);
-        for (i, src) in src_numbers.iter().enumerate() {
-            if already_mapped(&dst_number, i) {
-                continue;
-            }
+    let mut lines = vec![];
+    for &seed in &almanac.seeds {
+        lines.push(almanac.trace(seed)?.to_string());
+    }
+    if let Some(seed) = almanac.seeds_range_min_seed()? {
+        lines.push(format!("part two minimum: {}", almanac.trace(seed)?));
+    }
+    Ok(lines.join("\n"))
+}
 
-            if src_range.contains(src) {
-                dst_number[i] = Some(dst_range.start() + (*src - src_range.start()));
-            }
+// `--lint` reports suspicious map entries and how much each map covers
+pub fn run_lint(config: &Config) -> Result<String, Box<dyn Error>> {
+    let almanac: Almanac = fs::read_to_string(&config.file_path)?.parse()?;
+    almanac.validate()?;
+
+    Ok(lint::lint(&almanac).to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlmanacError {
+    Parse(String),
+    // more than one map converts from this category
+    DuplicateSource(String),
+    // this map cannot be reached from the seed category
+    Disconnected(String),
+    // following the maps comes back to this category
+    Cycle(String),
+    NoPath(String, String),
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlmanacError::Parse(line) => write!(f, "invalid almanac line: {line}"),
+            AlmanacError::DuplicateSource(cat) => write!(f, "more than one {cat} map"),
+            AlmanacError::Disconnected(cat) => write!(f, "{cat} map is not reachable from seed"),
+            AlmanacError::Cycle(cat) => write!(f, "maps loop back to {cat}"),
+            AlmanacError::NoPath(from, to) => write!(f, "no maps lead from {from} to {to}"),
         }
     }
-
-    collect_values(&src_numbers, dst_number.into_iter())
-        .into_iter()
-        .min()
-        .unwrap()
 }
 
-fn already_mapped(dst: &Vec<Option<u64>>, i: usize) -> bool {
-    matches!(dst[i], Some(_))
+impl Error for AlmanacError {}
+
+// One "destination source length" line of a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapEntry {
+    pub dst: u64,
+    pub src: u64,
+    pub len: u64,
 }
 
-fn initial_seeds<'a>(it: &mut impl Iterator<Item = &'a str>) -> Vec<u64> {
-    it.next()
-        .into_iter()
-        .flat_map(|s| s.strip_prefix("seeds: "))
-        .flat_map(|s| s.split_whitespace())
-        .flat_map(|s| s.parse())
-        .collect()
+impl MapEntry {
+    // Source values the entry maps: nothing when its length is zero, and
+    // only up to where either the source or destination would pass u64::MAX.
+    pub fn src_range(&self) -> Option<RangeInclusive<u64>> {
+        let last = (self.len.checked_sub(1)?)
+            .min(u64::MAX - self.src)
+            .min(u64::MAX - self.dst);
+        Some(self.src..=self.src + last)
+    }
 }
 
-fn collect_values<'a>(src: &'a Vec<u64>, dst: impl Iterator<Item = Option<u64>>) -> Vec<u64> {
-    dst.enumerate().map(|(i, v)| v.unwrap_or(src[i])).collect()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryMap {
+    pub from: String,
+    pub to: String,
+    pub entries: Vec<MapEntry>,
 }
 
-pub fn seeds_range_min_location(almanac: &str) -> u64 {
-    let mut lines = almanac.lines();
-    let mut source_ranges = initial_seed_ranges(&mut lines);
-    let mut unmapped_ranges: Vec<RangeInclusive<u64>> = vec![];
-    let mut mapped_ranges: Vec<RangeInclusive<u64>> = vec![];
-
-    // skips first seed-to-soil map
-    for line in lines.filter(|l| !l.trim_start().is_empty()).skip(1) {
-        if unmapped_ranges.len() > 0 {
-            source_ranges.extend(unmapped_ranges.drain(..));
-        }
+impl CategoryMap {
+    // the first entry covering `value` wins; unmapped values pass through
+    pub fn get(&self, value: u64) -> u64 {
+        self.find(value).map_or(value, |i| {
+            self.entries[i].dst + (value - self.entries[i].src)
+        })
+    }
 
-        if line.ends_with("map:") {
-            source_ranges.append(&mut mapped_ranges);
-            continue;
-        }
+    // index of the entry `get` uses for `value`
+    pub fn find(&self, value: u64) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.src_range().is_some_and(|r| r.contains(&value)))
+    }
 
-        let (map_dst_range, map_src_range, len) = ranges_from(line);
+    // Maps whole ranges at once, with the same semantics as `get` for each
+    // value: where entries overlap the first one listed wins, and what no
+    // entry covers keeps its value. The result is sorted and merged.
+    pub fn map_ranges(&self, ranges: &[RangeInclusive<u64>]) -> Vec<RangeInclusive<u64>> {
+        RangeMap::from(self).image(ranges)
+    }
+}
 
-        for src_range in source_ranges.drain(..) {
-            if !overlaps(&src_range, &map_src_range) {
-                unmapped_ranges.push(src_range);
-                continue; // keep non overlapping ranges untouched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub maps: Vec<CategoryMap>,
+}
+
+const SEED: &str = "seed";
+const LOCATION: &str = "location";
+
+impl FromStr for Almanac {
+    type Err = AlmanacError;
+
+    fn from_str(s: &str) -> Result<Almanac, AlmanacError> {
+        let mut lines = s.lines().filter(|l| !l.trim().is_empty());
+        let seeds_line = lines.next().unwrap_or_default();
+        let seeds = seeds_line
+            .strip_prefix("seeds:")
+            .and_then(parse_numbers)
+            .ok_or_else(|| AlmanacError::Parse(seeds_line.to_string()))?;
+
+        let mut maps: Vec<CategoryMap> = vec![];
+        for line in lines {
+            let parse_err = || AlmanacError::Parse(line.to_string());
+
+            if let Some(header) = line.trim().strip_suffix(" map:") {
+                let (from, to) = header.split_once("-to-").ok_or_else(parse_err)?;
+                maps.push(CategoryMap {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    entries: vec![],
+                });
+                continue;
             }
 
-            let mut start = *map_dst_range.start();
+            let entry = match parse_numbers(line).as_deref() {
+                Some(&[dst, src, len]) => MapEntry { dst, src, len },
+                _ => return Err(parse_err()),
+            };
+            maps.last_mut().ok_or_else(parse_err)?.entries.push(entry);
+        }
+
+        Ok(Almanac { seeds, maps })
+    }
+}
 
-            // source range start endpoint is whithin source range map
-            if map_src_range.end() - src_range.start() <= len {
-                start = map_dst_range.start() + (len - (map_src_range.end() - src_range.start())) - 1;
-            } else {
-                unmapped_ranges.push(*src_range.start()..=*map_src_range.start() - 1);
-            }
+fn parse_numbers(s: &str) -> Option<Vec<u64>> {
+    s.split_whitespace().map(|n| n.parse().ok()).collect()
+}
 
-            let mut end = *map_dst_range.end();
+impl Almanac {
+    // Checks the maps form a single chain starting at the seed category,
+    // in whichever order they are listed.
+    pub fn validate(&self) -> Result<(), AlmanacError> {
+        let mut by_source = HashMap::new();
+        for map in &self.maps {
+            if by_source.insert(map.from.as_str(), map).is_some() {
+                return Err(AlmanacError::DuplicateSource(map.from.clone()));
+            }
+        }
 
-            // source range end endpoint is whithin source range map
-            if src_range.end() - map_src_range.start() <= len {
-                end = map_dst_range.start() + (src_range.end() - map_src_range.start());
-            } else {
-                unmapped_ranges.push(*map_src_range.end() + 1..=*src_range.end());
+        let mut visited = HashSet::new();
+        let mut category = SEED;
+        while let Some(map) = by_source.get(category) {
+            if !visited.insert(category) {
+                return Err(AlmanacError::Cycle(category.to_string()));
             }
+            category = &map.to;
+        }
 
-            mapped_ranges.push(start..=end);
+        match self
+            .maps
+            .iter()
+            .find(|m| !visited.contains(m.from.as_str()))
+        {
+            Some(map) => Err(AlmanacError::Disconnected(map.from.clone())),
+            None => Ok(()),
         }
     }
 
-    mapped_ranges
-        .iter()
-        .chain(&unmapped_ranges)
-        .map(|range| *range.start())
-        .min()
-        .unwrap()
-}
+    // The maps converting `from` into `to`, in order.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&CategoryMap>, AlmanacError> {
+        let mut path = vec![];
+        let mut category = from;
+        while category != to {
+            let map = self
+                .maps
+                .iter()
+                .find(|m| m.from == category)
+                // a longer path must be going around a cycle
+                .filter(|_| path.len() < self.maps.len())
+                .ok_or_else(|| AlmanacError::NoPath(from.to_string(), to.to_string()))?;
+            path.push(map);
+            category = &map.to;
+        }
+        Ok(path)
+    }
+
+    pub fn convert(&self, value: u64, from: &str, to: &str) -> Result<u64, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(value, |value, map| map.get(value)))
+    }
+
+    pub fn min_location(&self) -> Result<Option<u64>, AlmanacError> {
+        let path = self.path(SEED, LOCATION)?;
+        Ok(self
+            .seeds
+            .iter()
+            .map(|&seed| path.iter().fold(seed, |value, map| map.get(value)))
+            .min())
+    }
 
-fn initial_seed_ranges<'a>(it: &mut impl Iterator<Item = &'a str>) -> Vec<RangeInclusive<u64>> {
-    let (seeds, lens): (Vec<_>, Vec<_>) = it
-        .next()
-        .into_iter()
-        .flat_map(|s| s.strip_prefix("seeds: "))
-        .flat_map(|s| s.split_whitespace())
-        .flat_map(|s| s.parse::<u64>())
-        .enumerate()
-        .partition(|pair| pair.0 % 2 == 0);
+    // seeds read as (start, length) pairs; empty pairs are dropped and
+    // ranges running past u64::MAX stop there
+    pub fn seed_ranges(&self) -> Vec<RangeInclusive<u64>> {
+        let (seeds, lens): (Vec<_>, Vec<_>) = self
+            .seeds
+            .iter()
+            .enumerate()
+            .partition(|pair| pair.0 % 2 == 0);
+
+        zip(
+            seeds.into_iter().map(|pair| *pair.1),
+            lens.into_iter().map(|pair| *pair.1),
+        )
+        .filter(|&(_, len)| len > 0)
+        .map(|(seed, len)| seed..=seed.saturating_add(len - 1))
+        .collect()
+    }
+
+    // The maps from `from` to `to` collapsed into a single one.
+    pub fn range_map(&self, from: &str, to: &str) -> Result<RangeMap, AlmanacError> {
+        Ok(self
+            .path(from, to)?
+            .into_iter()
+            .fold(RangeMap::identity(), |range_map, map| {
+                range_map.then(&RangeMap::from(map))
+            }))
+    }
+
+    // Every seed, listed or not, ending up at `location`.
+    pub fn seeds_for_location(&self, location: u64) -> Result<Vec<u64>, AlmanacError> {
+        Ok(self.range_map(SEED, LOCATION)?.preimage(location))
+    }
+
+    // the seed within the seed ranges reaching the lowest location
+    pub fn seeds_range_min_seed(&self) -> Result<Option<u64>, AlmanacError> {
+        Ok(self
+            .range_map(SEED, LOCATION)?
+            .argmin_over(&self.seed_ranges())
+            .map(|(seed, _)| seed))
+    }
 
-    zip(
-        seeds.into_iter().map(|pair| pair.1),
-        lens.into_iter().map(|pair| pair.1),
-    )
-    .map(|(seed, len)| seed..=seed + len - 1)
-    .collect()
+    pub fn seeds_range_min_location(&self) -> Result<Option<u64>, AlmanacError> {
+        Ok(self
+            .range_map(SEED, LOCATION)?
+            .min_over(&self.seed_ranges()))
+    }
 }
 
-fn overlaps(a: &RangeInclusive<u64>, b: &RangeInclusive<u64>) -> bool {
-    b.start() <= a.end() && a.start() <= b.end()
+pub fn min_location(almanac: &str) -> u64 {
+    let almanac: Almanac = almanac.parse().unwrap();
+    almanac.min_location().unwrap().unwrap()
 }
 
-fn ranges_from(s: &str) -> (RangeInclusive<u64>, RangeInclusive<u64>, u64) {
-    let range: Vec<u64> = s.split_whitespace().flat_map(|s| s.parse()).collect();
-    (
-        range[0]..=range[0] + range[2] - 1,
-        range[1]..=range[1] + range[2] - 1,
-        range[2],
-    )
+pub fn seeds_range_min_location(almanac: &str) -> u64 {
+    let almanac: Almanac = almanac.parse().unwrap();
+    almanac.seeds_range_min_location().unwrap().unwrap()
 }
 
 #[cfg(test)]
@@ -231,4 +376,125 @@ humidity-to-location map:
 
         assert_eq!(46, seeds_range_min_location(almanac));
     }
+
+    #[test]
+    fn category_lookup() {
+        let almanac: Almanac = "\
+seeds: 79 14 55 13
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4"
+            .parse()
+            .unwrap();
+
+        assert_eq!(Ok(()), almanac.validate());
+        assert_eq!(Ok(81), almanac.convert(79, "seed", "soil"));
+        assert_eq!(Ok(81), almanac.convert(79, "seed", "water"));
+        assert_eq!(Ok(49), almanac.convert(53, "fertilizer", "water"));
+        assert_eq!(Ok(53), almanac.convert(14, "seed", "fertilizer"));
+        assert_eq!(
+            Err(AlmanacError::NoPath(
+                "water".to_string(),
+                "seed".to_string()
+            )),
+            almanac.convert(1, "water", "seed")
+        );
+    }
+
+    #[test]
+    fn broken_chain() {
+        let disconnected: Almanac = "\
+seeds: 1
+
+seed-to-soil map:
+1 2 3
+
+water-to-light map:
+1 2 3"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Err(AlmanacError::Disconnected("water".to_string())),
+            disconnected.validate()
+        );
+
+        let cycle: Almanac = "\
+seeds: 1
+
+seed-to-soil map:
+soil-to-seed map:"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Err(AlmanacError::Cycle("seed".to_string())),
+            cycle.validate()
+        );
+
+        assert!("seeds: 1\n1 2 3".parse::<Almanac>().is_err());
+        assert!("seeds: 1\nseed-to-soil map:\n1 2"
+            .parse::<Almanac>()
+            .is_err());
+    }
+
+    #[test]
+    fn seeds_for_location() {
+        let almanac: Almanac = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4"
+            .parse()
+            .unwrap();
+
+        assert_eq!(Ok(vec![82]), almanac.seeds_for_location(46));
+        assert_eq!(Ok(vec![13]), almanac.seeds_for_location(35));
+
+        let inverse = almanac
+            .range_map("seed", "location")
+            .unwrap()
+            .inverse()
+            .unwrap();
+        assert_eq!(79, inverse.get(82));
+    }
 }
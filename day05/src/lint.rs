@@ -0,0 +1,372 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::{Almanac, CategoryMap, MapEntry};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    // the later entry never applies to `values`, the earlier one wins
+    OverlappingSources {
+        map: String,
+        first: usize,
+        second: usize,
+        values: RangeInclusive<u64>,
+    },
+    // two entries send different sources to the same `values`, so the map
+    // is not injective
+    OverlappingDestinations {
+        map: String,
+        first: usize,
+        second: usize,
+        values: RangeInclusive<u64>,
+    },
+    // `values` are both an entry's destination and sources no entry maps,
+    // which keep their value, so the map is not injective either
+    OverlappingPassThrough {
+        map: String,
+        entry: usize,
+        values: RangeInclusive<u64>,
+    },
+    ZeroLength {
+        map: String,
+        entry: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::OverlappingSources {
+                map,
+                first,
+                second,
+                values,
+            } => write!(
+                f,
+                "{map}: lines {} and {} both map sources {}-{}, line {} is shadowed",
+                first + 1,
+                second + 1,
+                values.start(),
+                values.end(),
+                second + 1
+            ),
+            Issue::OverlappingDestinations {
+                map,
+                first,
+                second,
+                values,
+            } => write!(
+                f,
+                "{map}: lines {} and {} both map onto {}-{}",
+                first + 1,
+                second + 1,
+                values.start(),
+                values.end()
+            ),
+            Issue::OverlappingPassThrough { map, entry, values } => write!(
+                f,
+                "{map}: line {} maps onto {}-{}, which unmapped sources keep",
+                entry + 1,
+                values.start(),
+                values.end()
+            ),
+            Issue::ZeroLength { map, entry } => {
+                write!(f, "{map}: line {} has length zero", entry + 1)
+            }
+        }
+    }
+}
+
+// How much of the source space between its lowest and highest entry a map
+// covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub map: String,
+    pub entries: usize,
+    // distinct source values some entry maps
+    pub covered: u128,
+    pub span: Option<RangeInclusive<u64>>,
+    // source values inside the span left unmapped
+    pub gaps: Vec<RangeInclusive<u64>>,
+}
+
+impl Coverage {
+    pub fn span_len(&self) -> u128 {
+        self.span.as_ref().map_or(0, len)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+    pub coverage: Vec<Coverage>,
+}
+
+pub fn lint(almanac: &Almanac) -> Report {
+    let mut issues = vec![];
+    let mut coverage = vec![];
+
+    for map in &almanac.maps {
+        let map_coverage = coverage_of(map);
+        issues.extend(lint_map(map, &map_coverage));
+        coverage.push(map_coverage);
+    }
+
+    Report { issues, coverage }
+}
+
+fn name(map: &CategoryMap) -> String {
+    format!("{}-to-{}", map.from, map.to)
+}
+
+fn dst_range(entry: &MapEntry) -> Option<RangeInclusive<u64>> {
+    let src = entry.src_range()?;
+    Some(entry.dst..=entry.dst + (src.end() - src.start()))
+}
+
+fn intersection(a: &RangeInclusive<u64>, b: &RangeInclusive<u64>) -> Option<RangeInclusive<u64>> {
+    let start = *a.start().max(b.start());
+    let end = *a.end().min(b.end());
+    (start <= end).then_some(start..=end)
+}
+
+fn len(r: &RangeInclusive<u64>) -> u128 {
+    (r.end() - r.start()) as u128 + 1
+}
+
+// Every source value no entry maps, inside the span or around it.
+fn unmapped(coverage: &Coverage) -> Vec<RangeInclusive<u64>> {
+    let Some(span) = &coverage.span else {
+        return vec![0..=u64::MAX];
+    };
+    let mut unmapped = vec![];
+    if *span.start() > 0 {
+        unmapped.push(0..=span.start() - 1);
+    }
+    unmapped.extend(coverage.gaps.iter().cloned());
+    if *span.end() < u64::MAX {
+        unmapped.push(span.end() + 1..=u64::MAX);
+    }
+    unmapped
+}
+
+fn lint_map(map: &CategoryMap, coverage: &Coverage) -> Vec<Issue> {
+    let mut issues = vec![];
+    let unmapped = unmapped(coverage);
+
+    for (i, a) in map.entries.iter().enumerate() {
+        if a.len == 0 {
+            issues.push(Issue::ZeroLength {
+                map: name(map),
+                entry: i,
+            });
+        }
+        if let Some(dst) = dst_range(a) {
+            for values in unmapped.iter().filter_map(|u| intersection(&dst, u)) {
+                issues.push(Issue::OverlappingPassThrough {
+                    map: name(map),
+                    entry: i,
+                    values,
+                });
+            }
+        }
+
+        for (j, b) in map.entries.iter().enumerate().skip(i + 1) {
+            if let (Some(a), Some(b)) = (a.src_range(), b.src_range()) {
+                if let Some(values) = intersection(&a, &b) {
+                    issues.push(Issue::OverlappingSources {
+                        map: name(map),
+                        first: i,
+                        second: j,
+                        values,
+                    });
+                }
+            }
+            if let (Some(a), Some(b)) = (dst_range(a), dst_range(b)) {
+                if let Some(values) = intersection(&a, &b) {
+                    issues.push(Issue::OverlappingDestinations {
+                        map: name(map),
+                        first: i,
+                        second: j,
+                        values,
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+fn coverage_of(map: &CategoryMap) -> Coverage {
+    let mut ranges: Vec<RangeInclusive<u64>> =
+        map.entries.iter().filter_map(MapEntry::src_range).collect();
+    ranges.sort_by_key(|r| *r.start());
+
+    // merge overlapping and touching ranges, noting the holes between them
+    let mut merged: Vec<RangeInclusive<u64>> = vec![];
+    let mut gaps = vec![];
+    for r in ranges {
+        match merged.last_mut() {
+            Some(last) if *r.start() <= last.end().saturating_add(1) => {
+                *last = *last.start()..=*last.end().max(r.end());
+            }
+            Some(last) => {
+                gaps.push(last.end() + 1..=r.start() - 1);
+                merged.push(r);
+            }
+            None => merged.push(r),
+        }
+    }
+
+    Coverage {
+        map: name(map),
+        entries: map.entries.len(),
+        covered: merged.iter().map(len).sum(),
+        span: merged
+            .first()
+            .zip(merged.last())
+            .map(|(first, last)| *first.start()..=*last.end()),
+        gaps,
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "warning: {issue}")?;
+        }
+        if self.issues.is_empty() {
+            writeln!(f, "no issues")?;
+        }
+
+        writeln!(
+            f,
+            "{:<28} {:>7} {:>22} {:>22} {:>7} {:>5}",
+            "map", "entries", "covered", "span", "percent", "gaps"
+        )?;
+        for c in &self.coverage {
+            let span = c.span_len();
+            let percent = match span {
+                0 => 0.0,
+                _ => c.covered as f64 * 100.0 / span as f64,
+            };
+            writeln!(
+                f,
+                "{:<28} {:>7} {:>22} {:>22} {:>6.1}% {:>5}",
+                c.map,
+                c.entries,
+                c.covered,
+                span,
+                percent,
+                c.gaps.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn almanac(maps: &str) -> Almanac {
+        format!("seeds: 1\n\n{maps}").parse().unwrap()
+    }
+
+    #[test]
+    fn clean_map() {
+        let report = lint(&almanac(
+            "\
+seed-to-location map:
+50 98 2
+52 50 48",
+        ));
+
+        assert_eq!(Vec::<Issue>::new(), report.issues);
+        assert_eq!(
+            vec![Coverage {
+                map: "seed-to-location".to_string(),
+                entries: 2,
+                covered: 50,
+                span: Some(50..=99),
+                gaps: vec![],
+            }],
+            report.coverage
+        );
+    }
+
+    #[test]
+    fn overlapping_entries() {
+        let report = lint(&almanac(
+            "\
+seed-to-location map:
+100 0 10
+0 5 10
+105 20 5
+7 30 0",
+        ));
+
+        assert_eq!(
+            vec![
+                // 25 and up pass through unmapped
+                Issue::OverlappingPassThrough {
+                    map: "seed-to-location".to_string(),
+                    entry: 0,
+                    values: 100..=109,
+                },
+                Issue::OverlappingSources {
+                    map: "seed-to-location".to_string(),
+                    first: 0,
+                    second: 1,
+                    values: 5..=9,
+                },
+                Issue::OverlappingDestinations {
+                    map: "seed-to-location".to_string(),
+                    first: 0,
+                    second: 2,
+                    values: 105..=109,
+                },
+                Issue::OverlappingPassThrough {
+                    map: "seed-to-location".to_string(),
+                    entry: 2,
+                    values: 105..=109,
+                },
+                Issue::ZeroLength {
+                    map: "seed-to-location".to_string(),
+                    entry: 3,
+                },
+            ],
+            report.issues
+        );
+
+        let coverage = &report.coverage[0];
+        assert_eq!(20, coverage.covered);
+        assert_eq!(25, coverage.span_len());
+        assert_eq!(vec![15..=19], coverage.gaps);
+    }
+
+    #[test]
+    fn report_display() {
+        let report = lint(&almanac(
+            "\
+seed-to-soil map:
+0 10 5
+3 20 5
+
+soil-to-location map:",
+        ));
+        let report = report.to_string();
+
+        // 0 to 9 are left unmapped, so they keep their values
+        assert!(report.starts_with(
+            "\
+warning: seed-to-soil: line 1 maps onto 0-4, which unmapped sources keep
+warning: seed-to-soil: lines 1 and 2 both map onto 3-4
+warning: seed-to-soil: line 2 maps onto 3-7, which unmapped sources keep
+"
+        ));
+        assert!(report.contains("seed-to-soil"));
+        assert!(report.contains("66.7%"));
+    }
+}
@@ -9,6 +9,28 @@ fn main() {
         process::exit(1);
     });
 
+    if config.flag("trace") {
+        match day5::run_trace(&config) {
+            Ok(trace) => println!("{trace}"),
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if config.flag("lint") {
+        match day5::run_lint(&config) {
+            Ok(report) => print!("{report}"),
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     match day5::run(config) {
         Ok((part1, part2)) => println!("part 1: {:?}\npart 2: {:?}", part1, part2),
         Err(e) => {
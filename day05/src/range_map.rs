@@ -0,0 +1,412 @@
+use std::ops::RangeInclusive;
+
+use crate::CategoryMap;
+
+// Values in `src` map to `dst + (value - src.start())`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub src: RangeInclusive<u64>,
+    pub dst: u64,
+}
+
+impl Segment {
+    fn dst_end(&self) -> u64 {
+        self.dst + (self.src.end() - self.src.start())
+    }
+
+    fn get(&self, value: u64) -> u64 {
+        self.dst + (value - self.src.start())
+    }
+}
+
+// A piecewise-linear map over every u64: segments are sorted, contiguous and
+// cover 0..=u64::MAX, with unmapped stretches kept as identity segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeMap {
+    segments: Vec<Segment>,
+}
+
+impl RangeMap {
+    pub fn identity() -> RangeMap {
+        RangeMap {
+            segments: vec![Segment {
+                src: 0..=u64::MAX,
+                dst: 0,
+            }],
+        }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    // index of the segment holding `value`
+    fn find(&self, value: u64) -> usize {
+        self.segments.partition_point(|s| *s.src.end() < value)
+    }
+
+    pub fn get(&self, value: u64) -> u64 {
+        self.segments[self.find(value)].get(value)
+    }
+
+    // Replaces the mapping of `src` with one starting at `dst`.
+    fn overlay(&mut self, src: RangeInclusive<u64>, dst: u64) {
+        let (start, end) = (*src.start(), *src.end());
+        let mut segments = Vec::with_capacity(self.segments.len() + 2);
+        let mut placed = false;
+
+        for s in self.segments.drain(..) {
+            let (s_start, s_end) = (*s.src.start(), *s.src.end());
+            if s_end < start || end < s_start {
+                segments.push(s);
+                continue;
+            }
+            if s_start < start {
+                segments.push(Segment {
+                    src: s_start..=start - 1,
+                    dst: s.dst,
+                });
+            }
+            if !placed {
+                segments.push(Segment {
+                    src: src.clone(),
+                    dst,
+                });
+                placed = true;
+            }
+            if end < s_end {
+                segments.push(Segment {
+                    src: end + 1..=s_end,
+                    dst: s.get(end + 1),
+                });
+            }
+        }
+
+        self.segments = segments;
+    }
+
+    // Merges neighboring segments that continue the same line.
+    fn normalize(mut self) -> RangeMap {
+        let mut segments: Vec<Segment> = Vec::with_capacity(self.segments.len());
+        for s in self.segments.drain(..) {
+            if let Some(last) = segments.last_mut() {
+                if last.dst_end().checked_add(1) == Some(s.dst) {
+                    last.src = *last.src.start()..=*s.src.end();
+                    continue;
+                }
+            }
+            segments.push(s);
+        }
+        RangeMap { segments }
+    }
+
+    // Applies `self` first, then `next`.
+    pub fn then(&self, next: &RangeMap) -> RangeMap {
+        let mut segments = vec![];
+        for s in &self.segments {
+            let (lo, hi) = (s.dst, s.dst_end());
+            for t in &next.segments[next.find(lo)..] {
+                if *t.src.start() > hi {
+                    break;
+                }
+                let piece_lo = lo.max(*t.src.start());
+                let piece_hi = hi.min(*t.src.end());
+                segments.push(Segment {
+                    src: s.src.start() + (piece_lo - lo)..=s.src.start() + (piece_hi - lo),
+                    dst: t.get(piece_lo),
+                });
+            }
+        }
+        RangeMap { segments }.normalize()
+    }
+
+    // Every value mapped to `value`; maps with overlapping destinations
+    // can send several values to the same one.
+    pub fn preimage(&self, value: u64) -> Vec<u64> {
+        self.segments
+            .iter()
+            .filter(|s| s.dst <= value && value <= s.dst_end())
+            .map(|s| s.src.start() + (value - s.dst))
+            .collect()
+    }
+
+    // The inverse map, when every value has exactly one preimage.
+    pub fn inverse(&self) -> Option<RangeMap> {
+        let mut segments: Vec<Segment> = self
+            .segments
+            .iter()
+            .map(|s| Segment {
+                src: s.dst..=s.dst_end(),
+                dst: *s.src.start(),
+            })
+            .collect();
+        segments.sort_unstable_by_key(|s| *s.src.start());
+
+        let mut next = Some(0u64);
+        for s in &segments {
+            if Some(*s.src.start()) != next {
+                return None;
+            }
+            next = s.src.end().checked_add(1);
+        }
+        if next.is_some() {
+            return None;
+        }
+
+        Some(RangeMap { segments }.normalize())
+    }
+
+    // Values `ranges` map to, sorted with overlapping or adjacent ranges merged.
+    pub fn image(&self, ranges: &[RangeInclusive<u64>]) -> Vec<RangeInclusive<u64>> {
+        let mut image: Vec<RangeInclusive<u64>> = ranges
+            .iter()
+            .flat_map(|r| {
+                self.segments[self.find(*r.start())..]
+                    .iter()
+                    .take_while(move |s| s.src.start() <= r.end())
+                    .map(move |s| {
+                        s.get(*r.start().max(s.src.start()))..=s.get(*r.end().min(s.src.end()))
+                    })
+            })
+            .collect();
+        image.sort_unstable_by_key(|r| *r.start());
+
+        let mut merged: Vec<RangeInclusive<u64>> = Vec::with_capacity(image.len());
+        for r in image {
+            match merged.last_mut() {
+                Some(last) if last.end().checked_add(1).is_none_or(|n| n >= *r.start()) => {
+                    *last = *last.start()..=*last.end().max(r.end());
+                }
+                _ => merged.push(r),
+            }
+        }
+        merged
+    }
+
+    // Smallest value any of `ranges` maps to.
+    pub fn min_over(&self, ranges: &[RangeInclusive<u64>]) -> Option<u64> {
+        self.argmin_over(ranges).map(|(_, min)| min)
+    }
+
+    // The value in `ranges` with the smallest image, along with that image;
+    // ties go to the lowest value.
+    pub fn argmin_over(&self, ranges: &[RangeInclusive<u64>]) -> Option<(u64, u64)> {
+        ranges
+            .iter()
+            .flat_map(|r| {
+                self.segments[self.find(*r.start())..]
+                    .iter()
+                    .take_while(move |s| s.src.start() <= r.end())
+                    .map(move |s| {
+                        let value = *r.start().max(s.src.start());
+                        (value, s.get(value))
+                    })
+            })
+            .min_by_key(|&(value, image)| (image, value))
+    }
+}
+
+impl From<&CategoryMap> for RangeMap {
+    fn from(map: &CategoryMap) -> RangeMap {
+        let mut range_map = RangeMap::identity();
+        // the first matching entry wins, so it goes on top
+        for entry in map.entries.iter().rev() {
+            if let Some(src) = entry.src_range() {
+                range_map.overlay(src, entry.dst);
+            }
+        }
+        range_map.normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapEntry;
+
+    fn map(entries: &[(u64, u64, u64)]) -> CategoryMap {
+        CategoryMap {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            entries: entries
+                .iter()
+                .map(|&(dst, src, len)| MapEntry { dst, src, len })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn from_category_map() {
+        let category_map = map(&[(50, 98, 2), (52, 50, 48)]);
+        let range_map = RangeMap::from(&category_map);
+
+        for value in 0..120 {
+            assert_eq!(category_map.get(value), range_map.get(value));
+        }
+        assert_eq!(u64::MAX, range_map.get(u64::MAX));
+    }
+
+    #[test]
+    fn first_entry_wins() {
+        let category_map = map(&[(100, 10, 5), (200, 0, 20)]);
+        let range_map = RangeMap::from(&category_map);
+
+        assert_eq!(205, range_map.get(5));
+        assert_eq!(102, range_map.get(12));
+        assert_eq!(215, range_map.get(15));
+        assert_eq!(None, range_map.inverse());
+    }
+
+    #[test]
+    fn compose_and_invert() {
+        let first = RangeMap::from(&map(&[(50, 98, 2), (52, 50, 48)]));
+        let second = RangeMap::from(&map(&[(0, 15, 37), (37, 52, 2), (39, 0, 15)]));
+        let composed = first.then(&second);
+
+        for value in 0..120 {
+            assert_eq!(second.get(first.get(value)), composed.get(value));
+        }
+
+        let inverse = composed.inverse().unwrap();
+        for value in 0..120 {
+            assert_eq!(value, inverse.get(composed.get(value)));
+            assert_eq!(vec![value], composed.preimage(composed.get(value)));
+        }
+        assert_eq!(RangeMap::identity(), composed.then(&inverse));
+    }
+
+    #[test]
+    fn overlapping_destinations() {
+        let range_map = RangeMap::from(&map(&[(0, 10, 5)]));
+
+        assert_eq!(vec![2, 12], range_map.preimage(2));
+        assert_eq!(Some(0), range_map.min_over(&[8..=20]));
+        assert_eq!(Some(15), range_map.min_over(&[15..=20]));
+    }
+
+    // xorshift, so the property tests below are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        // values clustered at both ends of the u64 range
+        fn value(&mut self) -> u64 {
+            let offset = self.below(48);
+            if self.below(2) == 0 {
+                offset
+            } else {
+                u64::MAX - offset
+            }
+        }
+
+        fn map(&mut self) -> CategoryMap {
+            let entries: Vec<(u64, u64, u64)> = (0..self.below(6))
+                .map(|_| (self.value(), self.value(), self.below(24)))
+                .collect();
+            map(&entries)
+        }
+
+        fn range(&mut self) -> RangeInclusive<u64> {
+            let start = self.value();
+            start..=start.saturating_add(self.below(24))
+        }
+    }
+
+    fn brute_force_image(map: &CategoryMap, ranges: &[RangeInclusive<u64>]) -> Vec<u64> {
+        let mut values: Vec<u64> = ranges
+            .iter()
+            .flat_map(|r| r.clone().map(|v| map.get(v)))
+            .collect();
+        values.sort_unstable();
+        values.dedup();
+        values
+    }
+
+    #[test]
+    fn entry_edge_cases() {
+        let category_map = map(&[(5, 0, 0), (0, u64::MAX - 1, 10), (u64::MAX - 1, 3, 10)]);
+        let range_map = RangeMap::from(&category_map);
+
+        // empty entry maps nothing
+        assert_eq!(0, range_map.get(0));
+        // source running past u64::MAX is cut at it
+        assert_eq!(1, range_map.get(u64::MAX));
+        // so is a destination
+        assert_eq!(u64::MAX, range_map.get(4));
+        assert_eq!(5, range_map.get(5));
+        assert_eq!(
+            vec![0..=1, 5..=5, u64::MAX - 1..=u64::MAX - 1],
+            range_map.image(&[u64::MAX - 1..=u64::MAX, 5..=5, 3..=3])
+        );
+    }
+
+    #[test]
+    fn property_get_matches_first_entry_scan() {
+        let mut rng = Rng(0x5eed_2023);
+        for _ in 0..500 {
+            let category_map = rng.map();
+            let range_map = RangeMap::from(&category_map);
+            for _ in 0..50 {
+                let value = rng.value();
+                assert_eq!(
+                    category_map.get(value),
+                    range_map.get(value),
+                    "{category_map:?} at {value}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn property_ranges_match_per_value_mapping() {
+        let mut rng = Rng(0xa1_3a_4a_c0);
+        for _ in 0..500 {
+            let category_map = rng.map();
+            let ranges: Vec<_> = (0..1 + rng.below(4)).map(|_| rng.range()).collect();
+
+            let image: Vec<u64> = category_map
+                .map_ranges(&ranges)
+                .into_iter()
+                .flatten()
+                .collect();
+            assert_eq!(
+                brute_force_image(&category_map, &ranges),
+                image,
+                "{category_map:?} over {ranges:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn property_composition_matches_stages() {
+        let mut rng = Rng(0xc0_4905e);
+        for _ in 0..300 {
+            let stages: Vec<CategoryMap> = (0..1 + rng.below(4)).map(|_| rng.map()).collect();
+            let composed = stages.iter().fold(RangeMap::identity(), |m, stage| {
+                m.then(&RangeMap::from(stage))
+            });
+            let ranges: Vec<_> = (0..1 + rng.below(3)).map(|_| rng.range()).collect();
+
+            let brute_force = ranges
+                .iter()
+                .flat_map(|r| r.clone())
+                .map(|v| stages.iter().fold(v, |v, stage| stage.get(v)))
+                .min();
+            assert_eq!(
+                brute_force,
+                composed.min_over(&ranges),
+                "{stages:?} over {ranges:?}"
+            );
+        }
+    }
+}
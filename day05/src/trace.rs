@@ -0,0 +1,126 @@
+use std::fmt;
+
+use crate::{Almanac, AlmanacError, LOCATION, SEED};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub category: String,
+    pub value: u64,
+    // index of the map entry that produced `value`, None when it passed
+    // through unmapped or for the starting seed
+    pub entry: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace(pub Vec<Step>);
+
+impl Almanac {
+    // The value of `seed` at each category on its way to location.
+    pub fn trace(&self, seed: u64) -> Result<Trace, AlmanacError> {
+        let mut steps = vec![Step {
+            category: SEED.to_string(),
+            value: seed,
+            entry: None,
+        }];
+
+        let mut value = seed;
+        for map in self.path(SEED, LOCATION)? {
+            let entry = map.find(value);
+            value = map.get(value);
+            steps.push(Step {
+                category: map.to.clone(),
+                value,
+                entry,
+            });
+        }
+
+        Ok(Trace(steps))
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let steps: Vec<String> = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, step)| match (i, step.entry) {
+                (0, _) => format!("{} {}", step.category, step.value),
+                (_, Some(entry)) => {
+                    format!("{} {} (line {})", step.category, step.value, entry + 1)
+                }
+                (_, None) => format!("{} {} (unmapped)", step.category, step.value),
+            })
+            .collect();
+        write!(f, "{}", steps.join(" -> "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn almanac() -> Almanac {
+        "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn trace_seed() {
+        let Trace(steps) = almanac().trace(79).unwrap();
+
+        let values: Vec<u64> = steps.iter().map(|s| s.value).collect();
+        assert_eq!(vec![79, 81, 81, 81, 74, 78, 78, 82], values);
+        assert_eq!(Some(1), steps[1].entry);
+        assert_eq!(None, steps[2].entry);
+        assert_eq!("location", steps[7].category);
+    }
+
+    #[test]
+    fn trace_display() {
+        assert_eq!(
+            "seed 14 -> soil 14 (unmapped) -> fertilizer 53 (line 3) -> water 49 (line 1) \
+-> light 42 (line 2) -> temperature 42 (unmapped) -> humidity 43 (line 2) -> location 43 (unmapped)",
+            almanac().trace(14).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn part_two_seed() {
+        assert_eq!(Ok(Some(82)), almanac().seeds_range_min_seed());
+    }
+}
@@ -1,75 +1,239 @@
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::iter::zip;
+use std::str::FromStr;
 
 use aoc::Config;
 
-pub fn run(config: Config) -> Result<(Option<u32>, Option<u32>), Box<dyn Error>> {
-    let sheet = fs::read_to_string(config.file_path)?;
+pub mod model;
+pub mod report;
 
-    let part_one = error_margin(&sheet);
-    let part_two = big_error_margin(&sheet);
+pub fn run(config: Config) -> Result<(Option<u128>, Option<u128>), Box<dyn Error>> {
+    let sheet: RaceSheet = fs::read_to_string(&config.file_path)?.parse()?;
+    // `--model` swaps the default hold-to-accelerate rule, see model::model
+    let model = config.value("model").map(model::model).transpose()?;
+    let wins = |&(time, record): &(u128, u128)| match &model {
+        Some(model) => model::count_wins(model.as_ref(), time, record),
+        None => ways_to_win(time, record),
+    };
+
+    let part_one = sheet.races.iter().map(wins).product();
+    let part_two = wins(&sheet.long_race);
 
     Ok((Some(part_one), Some(part_two)))
 }
 
-pub fn error_margin(sheet: &str) -> u32 {
-    list_races(sheet.lines())
-        .iter()
-        .map(|(max_time, max_dist)| {
-            (1..*max_time).fold(0, |acc, i| {
-                if i * (*max_time - i) <= *max_dist {
-                    return acc;
-                }
-                acc + 1
-            })
+// `--report` prints every race and then the single long race, as a table or
+// with `--json`
+pub fn run_report(config: &Config) -> Result<String, Box<dyn Error>> {
+    let sheet: RaceSheet = fs::read_to_string(&config.file_path)?.parse()?;
+    let model = model::model(config.value("model").unwrap_or("linear"))?;
+
+    let reports = report::Reports(
+        sheet
+            .races
+            .iter()
+            .chain([&sheet.long_race])
+            .map(|&(time, record)| report::report(model.as_ref(), time, record))
+            .collect(),
+    );
+    if config.flag("json") {
+        return Ok(reports.to_json());
+    }
+    Ok(reports.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SheetError {
+    Missing(&'static str),
+    Repeated { label: &'static str, line: usize },
+    Value { line: usize, token: String },
+    CountMismatch { times: usize, records: usize },
+    NoRaces,
+    // the digits of a line read as one number do not fit in a u128
+    TooLong(&'static str),
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SheetError::Missing(label) => write!(f, "no {label} line"),
+            SheetError::Repeated { label, line } => {
+                write!(f, "line {line}: second {label} line")
+            }
+            SheetError::Value { line, token } => write!(f, "line {line}: invalid value {token:?}"),
+            SheetError::CountMismatch { times, records } => {
+                write!(f, "{times} times but {records} distances")
+            }
+            SheetError::NoRaces => write!(f, "the sheet lists no races"),
+            SheetError::TooLong(label) => write!(f, "{label} of the long race overflows u128"),
+        }
+    }
+}
+
+impl Error for SheetError {}
+
+// The races of a sheet, as (time, record distance) pairs, and the single
+// race read by ignoring the spaces between digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceSheet {
+    pub races: Vec<(u128, u128)>,
+    pub long_race: (u128, u128),
+}
+
+const TIME: &str = "Time";
+const DISTANCE: &str = "Distance";
+
+impl FromStr for RaceSheet {
+    type Err = SheetError;
+
+    // "Time:" and "Distance:" lines may come in either order, with any
+    // spacing; "#" starts a comment and any other line is skipped as a
+    // header.
+    fn from_str(s: &str) -> Result<RaceSheet, SheetError> {
+        let mut times: Option<Vec<&str>> = None;
+        let mut records: Option<Vec<&str>> = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((label, values)) = line.split_once(':') else {
+                continue;
+            };
+            let (label, slot) = match label.trim() {
+                l if l.eq_ignore_ascii_case(TIME) => (TIME, &mut times),
+                l if l.eq_ignore_ascii_case(DISTANCE) => (DISTANCE, &mut records),
+                _ => continue,
+            };
+            if slot.is_some() {
+                return Err(SheetError::Repeated {
+                    label,
+                    line: line_no,
+                });
+            }
+
+            let values: Vec<&str> = values.split_whitespace().collect();
+            if let Some(token) = values
+                .iter()
+                .find(|t| !t.bytes().all(|b| b.is_ascii_digit()) || t.parse::<u128>().is_err())
+            {
+                return Err(SheetError::Value {
+                    line: line_no,
+                    token: token.to_string(),
+                });
+            }
+            *slot = Some(values);
+        }
+
+        let times = times.ok_or(SheetError::Missing(TIME))?;
+        let records = records.ok_or(SheetError::Missing(DISTANCE))?;
+        if times.len() != records.len() {
+            return Err(SheetError::CountMismatch {
+                times: times.len(),
+                records: records.len(),
+            });
+        }
+        if times.is_empty() {
+            return Err(SheetError::NoRaces);
+        }
+
+        let joined = |values: &[&str], label| {
+            values
+                .concat()
+                .parse()
+                .map_err(|_| SheetError::TooLong(label))
+        };
+        Ok(RaceSheet {
+            races: zip(&times, &records)
+                .map(|(t, r)| (t.parse().unwrap(), r.parse().unwrap()))
+                .collect(),
+            long_race: (joined(&times, TIME)?, joined(&records, DISTANCE)?),
         })
+    }
+}
+
+pub fn error_margin(sheet: &str) -> u128 {
+    let sheet: RaceSheet = sheet.parse().unwrap();
+    sheet
+        .races
+        .iter()
+        .map(|&(max_time, max_dist)| ways_to_win(max_time, max_dist))
         .product()
 }
 
-fn list_races<'a>(mut it: impl Iterator<Item = &'a str>) -> Vec<(u32, u32)> {
-    zip(
-        strip_and_parse("Time:", &mut it),
-        strip_and_parse("Distance:", &mut it),
-    )
-    .collect()
+pub fn big_error_margin(sheet: &str) -> u128 {
+    let sheet: RaceSheet = sheet.parse().unwrap();
+    let (max_time, max_dist) = sheet.long_race;
+    ways_to_win(max_time, max_dist)
 }
 
-fn strip_and_parse<'a>(prefix: &str, mut from: impl Iterator<Item = &'a str>) -> Vec<u32> {
-    from.next()
-        .iter()
-        .flat_map(|s| s.strip_prefix(prefix))
-        .flat_map(|s| s.split_whitespace())
-        .flat_map(|s| s.parse())
-        .collect::<Vec<u32>>()
+// Number of hold times h in 0..=time with h * (time - h) > record.
+pub fn ways_to_win(time: u128, record: u128) -> u128 {
+    // the distance peaks at half the time, and is symmetric around it
+    let half = time / 2;
+    if !beats(time, record, half) {
+        return 0;
+    }
+    let first = first_win(time, record, half);
+    time - 2 * first + 1
 }
 
-pub fn big_error_margin(sheet: &str) -> u32 {
-    let (max_time, max_dist) = race_time_dist(sheet.lines());
-    (1..max_time).fold(0, |acc, i| {
-        if i * (max_time - i) <= max_dist {
-            return acc;
-        }
-        acc + 1
-    })
+fn beats(time: u128, record: u128, hold: u128) -> bool {
+    // a product past u128::MAX is beyond any record
+    hold.checked_mul(time - hold)
+        .is_none_or(|dist| dist > record)
 }
 
-fn race_time_dist<'a>(mut it: impl Iterator<Item = &'a str>) -> (u64, u64) {
-    (
-        strip_parse_one("Time:", &mut it),
-        strip_parse_one("Distance:", &mut it),
-    )
+// The shortest winning hold time, given that holding for `half` wins.
+fn first_win(time: u128, record: u128, half: u128) -> u128 {
+    // h * (time - h) = record at h = (time - sqrt(time^2 - 4 * record)) / 2;
+    // isqrt rounds down, so the estimate is off by at most one either way
+    let estimate = time
+        .checked_mul(time)
+        .zip(record.checked_mul(4))
+        .map(|(square, record)| (time - isqrt(square - record)) / 2);
+
+    match estimate {
+        Some(mut hold) => {
+            while hold > 0 && beats(time, record, hold - 1) {
+                hold -= 1;
+            }
+            while !beats(time, record, hold) {
+                hold += 1;
+            }
+            hold
+        }
+        // too large to square: bisect for the first winning hold instead
+        None => {
+            let (mut lo, mut hi) = (0, half);
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                if beats(time, record, mid) {
+                    hi = mid;
+                } else {
+                    lo = mid + 1;
+                }
+            }
+            lo
+        }
+    }
 }
 
-fn strip_parse_one<'a>(prefix: &str, mut from: impl Iterator<Item = &'a str>) -> u64 {
-    from.next()
-        .iter()
-        .flat_map(|s| s.strip_prefix(prefix))
-        .flat_map(|s| s.split_whitespace())
-        .collect::<Vec<&str>>()
-        .join("")
-        .parse()
-        .unwrap()
+// floor(sqrt(n)), exactly
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from a power of two above the root only ever decreases
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (x + n / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
 }
 
 #[cfg(test)]
@@ -95,4 +259,113 @@ Distance:  9  40  200
 
         assert_eq!(71503, big_error_margin(sheet));
     }
+
+    #[test]
+    fn flexible_sheet() {
+        let sheet: RaceSheet = "\
+Race sheet, day 6
+# records from last year
+Distance:   9 40    200   # meters
+  time: 7    15 30
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(vec![(7, 9), (15, 40), (30, 200)], sheet.races);
+        assert_eq!((71530, 940200), sheet.long_race);
+    }
+
+    #[test]
+    fn sheet_errors() {
+        let parse = |s: &str| s.parse::<RaceSheet>().unwrap_err();
+
+        assert_eq!(SheetError::Missing("Distance"), parse("Time: 7"));
+        assert_eq!(
+            SheetError::CountMismatch {
+                times: 3,
+                records: 2
+            },
+            parse("Time: 7 15 30\nDistance: 9 40")
+        );
+        assert_eq!(
+            SheetError::Value {
+                line: 2,
+                token: "4O".to_string()
+            },
+            parse("Time: 7 15\nDistance: 9 4O")
+        );
+        assert_eq!(
+            SheetError::Value {
+                line: 1,
+                token: "-7".to_string()
+            },
+            parse("Time: -7\nDistance: 9")
+        );
+        assert_eq!(
+            SheetError::Repeated {
+                label: "Time",
+                line: 3
+            },
+            parse("Time: 7\nDistance: 9\nTime: 8")
+        );
+        assert_eq!(SheetError::NoRaces, parse("Time:\nDistance:"));
+        assert_eq!(
+            SheetError::TooLong("Time"),
+            parse(&format!("Time: {0} {0}\nDistance: 1 2", u128::MAX))
+        );
+    }
+
+    fn brute_force(time: u128, record: u128) -> u128 {
+        (0..=time).filter(|h| h * (time - h) > record).count() as u128
+    }
+
+    #[test]
+    fn closed_form_matches_brute_force() {
+        for time in 0..120 {
+            for record in 0..=time * time / 4 + 2 {
+                assert_eq!(
+                    brute_force(time, record),
+                    ways_to_win(time, record),
+                    "time {time}, record {record}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn isqrt_exact() {
+        for n in (0..10_000).chain([u64::MAX as u128, u128::MAX - 1, u128::MAX]) {
+            let root = isqrt(n);
+            assert!(root * root <= n);
+            assert!((root + 1).checked_mul(root + 1).is_none_or(|sq| sq > n));
+        }
+        assert_eq!(u64::MAX as u128, isqrt(u128::MAX));
+    }
+
+    fn assert_boundary(time: u128, record: u128) {
+        let ways = ways_to_win(time, record);
+        let first = (time + 1 - ways) / 2;
+        let last = time - first;
+
+        assert!(beats(time, record, first) && beats(time, record, last));
+        assert!(!beats(time, record, first - 1) && !beats(time, record, last + 1));
+    }
+
+    #[test]
+    fn large_races() {
+        // the u32 products of the brute force overflowed here
+        assert_eq!(
+            brute_force(100_000, 2_000_000_000),
+            ways_to_win(100_000, 2_000_000_000)
+        );
+
+        let time = u64::MAX as u128;
+        assert_eq!(time - 1, ways_to_win(time, 0));
+        assert_eq!(0, ways_to_win(time, time * time));
+        assert_boundary(time, 12_345_678_901_234_567_890_123);
+
+        // too large to square, so solved by bisection
+        assert_boundary(u128::MAX / 3, u128::MAX - 12345);
+        assert_boundary(1 << 100, 1 << 120);
+    }
 }
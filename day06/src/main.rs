@@ -9,6 +9,17 @@ fn main() {
         process::exit(1);
     });
 
+    if config.flag("report") {
+        match day6::run_report(&config) {
+            Ok(report) => println!("{report}"),
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     match day6::run(config) {
         Ok((part1, part2)) => println!("part 1: {:?}\npart 2: {:?}", part1, part2),
         Err(e) => {
@@ -0,0 +1,315 @@
+use std::ops::RangeInclusive;
+
+// Speed gained by holding the button, and the distance it makes the boat
+// travel in the rest of the race.
+pub trait BoatModel {
+    // None when the speed does not fit in a u128
+    fn speed(&self, hold: u128) -> Option<u128>;
+
+    // None when the distance does not fit in a u128
+    fn distance(&self, hold: u128, time: u128) -> Option<u128> {
+        match time - hold {
+            0 => Some(0),
+            rest => self.speed(hold)?.checked_mul(rest),
+        }
+    }
+
+    // Whether, for any race time, the distance rises to a single peak and
+    // then falls, so the solver may bisect; otherwise it scans every hold.
+    fn unimodal(&self) -> bool {
+        true
+    }
+}
+
+impl<F: Fn(u128) -> Option<u128>> BoatModel for F {
+    fn speed(&self, hold: u128) -> Option<u128> {
+        self(hold)
+    }
+}
+
+// `acceleration` millimeters per millisecond for each millisecond held
+pub struct Linear {
+    pub acceleration: u128,
+}
+
+impl BoatModel for Linear {
+    fn speed(&self, hold: u128) -> Option<u128> {
+        hold.checked_mul(self.acceleration)
+    }
+}
+
+// linear, but the boat never goes faster than `top_speed`
+pub struct Capped {
+    pub acceleration: u128,
+    pub top_speed: u128,
+}
+
+impl BoatModel for Capped {
+    fn speed(&self, hold: u128) -> Option<u128> {
+        Some(
+            hold.checked_mul(self.acceleration)
+                .map_or(self.top_speed, |speed| speed.min(self.top_speed)),
+        )
+    }
+}
+
+// The first millisecond adds `boost` speed and each one after it adds
+// `decay` less, until holding stops helping.
+pub struct Charging {
+    pub boost: u128,
+    pub decay: u128,
+}
+
+impl BoatModel for Charging {
+    fn speed(&self, hold: u128) -> Option<u128> {
+        // milliseconds that still add speed
+        let n = match self.decay {
+            0 => hold,
+            decay => hold.min(self.boost.div_ceil(decay)),
+        };
+        // boost + (boost - decay) + ... over n terms
+        let lost = if n % 2 == 0 {
+            (n / 2).checked_mul(n.saturating_sub(1))
+        } else {
+            n.checked_mul((n - 1) / 2)
+        };
+        n.checked_mul(self.boost)?
+            .checked_sub(lost?.checked_mul(self.decay)?)
+    }
+}
+
+// speed = c0 + c1 * hold + c2 * hold^2 + ...
+pub struct Polynomial(pub Vec<u128>);
+
+impl BoatModel for Polynomial {
+    fn speed(&self, hold: u128) -> Option<u128> {
+        self.0
+            .iter()
+            .rev()
+            .try_fold(0u128, |acc, &c| acc.checked_mul(hold)?.checked_add(c))
+    }
+
+    // c * hold^k and c0 + c1 * hold have log-concave speeds, which keeps the
+    // distance unimodal; a constant term next to higher powers does not,
+    // as with 100 + hold^2
+    fn unimodal(&self) -> bool {
+        let mut powers = self.0.iter().enumerate().filter(|&(_, &c)| c != 0);
+        powers.clone().count() <= 1 || powers.all(|(i, _)| i <= 1)
+    }
+}
+
+// Builds a model from "linear[:A]", "capped:TOP[:A]", "charging:BOOST:DECAY"
+// or "poly:C0,C1,...".
+pub fn model(spec: &str) -> Result<Box<dyn BoatModel>, String> {
+    let err = || format!("invalid boat model: {spec}");
+    let (name, args) = spec.split_once(':').unwrap_or((spec, ""));
+    let args: Vec<u128> = args
+        .split([':', ','])
+        .filter(|a| !a.is_empty())
+        .map(|a| a.trim().parse().map_err(|_| err()))
+        .collect::<Result<_, _>>()?;
+
+    match (name, args.as_slice()) {
+        ("linear", []) => Ok(Box::new(Linear { acceleration: 1 })),
+        ("linear", &[acceleration]) => Ok(Box::new(Linear { acceleration })),
+        ("capped", &[top_speed]) => Ok(Box::new(Capped {
+            acceleration: 1,
+            top_speed,
+        })),
+        ("capped", &[top_speed, acceleration]) => Ok(Box::new(Capped {
+            acceleration,
+            top_speed,
+        })),
+        ("charging", &[boost, decay]) => Ok(Box::new(Charging { boost, decay })),
+        ("poly", coefficients) if !coefficients.is_empty() => {
+            Ok(Box::new(Polynomial(coefficients.to_vec())))
+        }
+        _ => Err(err()),
+    }
+}
+
+// a distance past u128::MAX is beyond any record
+fn above(dist: Option<u128>, record: u128) -> bool {
+    dist.is_none_or(|dist| dist > record)
+}
+
+// First h in lo..hi for which `pred` holds, given it is false then true.
+fn partition(mut lo: u128, mut hi: u128, pred: impl Fn(u128) -> bool) -> u128 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+// Hold time with the longest distance, the shortest one on ties. Unimodal
+// models must rise (weakly only at the top) and then never rise again.
+pub fn peak(model: &dyn BoatModel, time: u128) -> u128 {
+    if !model.unimodal() {
+        // None is past u128::MAX, so longer than any other distance
+        let longer = |a: Option<u128>, b: Option<u128>| match (a, b) {
+            (Some(a), Some(b)) => a > b,
+            (a, b) => a.is_none() && b.is_some(),
+        };
+        return (1..=time).fold(0, |best, h| {
+            if longer(model.distance(h, time), model.distance(best, time)) {
+                h
+            } else {
+                best
+            }
+        });
+    }
+    partition(0, time, |h| {
+        match (model.distance(h + 1, time), model.distance(h, time)) {
+            (None, Some(_)) => false,
+            (Some(next), Some(dist)) => next <= dist,
+            _ => true,
+        }
+    })
+}
+
+// Hold times beating `record`, found by bisecting on either side of the
+// peak. For models that are not unimodal this is only the shortest and
+// longest winning holds, and some in between may lose.
+pub fn winning_holds(
+    model: &dyn BoatModel,
+    time: u128,
+    record: u128,
+) -> Option<RangeInclusive<u128>> {
+    let wins = |h: &u128| above(model.distance(*h, time), record);
+    if !model.unimodal() {
+        let first = (0..=time).find(wins)?;
+        let last = (0..=time).rev().find(wins)?;
+        return Some(first..=last);
+    }
+
+    let peak = peak(model, time);
+    if !above(model.distance(peak, time), record) {
+        return None;
+    }
+    let first = partition(0, peak, |h| above(model.distance(h, time), record));
+    // the last winning hold is the one after which the next one loses
+    let last = partition(peak, time, |h| !above(model.distance(h + 1, time), record));
+    Some(first..=last)
+}
+
+pub fn count_wins(model: &dyn BoatModel, time: u128, record: u128) -> u128 {
+    if !model.unimodal() {
+        return (0..=time)
+            .filter(|&h| above(model.distance(h, time), record))
+            .count() as u128;
+    }
+    winning_holds(model, time, record).map_or(0, |holds| holds.end() - holds.start() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ways_to_win;
+
+    fn brute_force(model: &dyn BoatModel, time: u128, record: u128) -> u128 {
+        (0..=time)
+            .filter(|&h| above(model.distance(h, time), record))
+            .count() as u128
+    }
+
+    fn check(model: &dyn BoatModel) {
+        for time in 0..60 {
+            let best = (0..=time)
+                .filter_map(|h| model.distance(h, time))
+                .max()
+                .unwrap_or(0);
+            for record in (0..=best + 1).step_by(((best / 50) as usize).max(1)) {
+                assert_eq!(
+                    brute_force(model, time, record),
+                    count_wins(model, time, record),
+                    "time {time}, record {record}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn linear_matches_closed_form() {
+        let model = Linear { acceleration: 1 };
+        for (time, record) in [(7, 9), (15, 40), (30, 200), (71530, 940200)] {
+            assert_eq!(ways_to_win(time, record), count_wins(&model, time, record));
+        }
+        check(&Linear { acceleration: 3 });
+    }
+
+    #[test]
+    fn models_match_brute_force() {
+        check(&Capped {
+            acceleration: 2,
+            top_speed: 11,
+        });
+        check(&Charging { boost: 9, decay: 2 });
+        check(&Charging { boost: 5, decay: 0 });
+        check(&Polynomial(vec![0, 1, 1]));
+        check(&Polynomial(vec![0, 0, 0, 1]));
+        // dips after hold 0 before rising again
+        check(&Polynomial(vec![100, 0, 1]));
+        check(&Polynomial(vec![0, 1, 0, 1]));
+        check(&|hold: u128| Some(2 * hold + 1));
+    }
+
+    #[test]
+    fn charging_speed() {
+        let model = Charging { boost: 5, decay: 2 };
+
+        // 5 + 3 + 1, then nothing more
+        let speeds: Vec<_> = (0..6).map(|h| model.speed(h).unwrap()).collect();
+        assert_eq!(vec![0, 5, 8, 9, 9, 9], speeds);
+    }
+
+    #[test]
+    fn overflowing_distances() {
+        let model = Polynomial(vec![0, 0, 0, 1]);
+        let time = 1 << 40;
+
+        // (2^40 - h) * h^3 overflows u128 around the peak
+        assert_eq!(None, model.distance(3 << 38, time));
+        assert_eq!(time - 1, count_wins(&model, time, 0));
+        assert_eq!(
+            brute_force(&model, 1000, 100_000_000_000),
+            count_wins(&model, 1000, 100_000_000_000)
+        );
+    }
+
+    #[test]
+    fn longest_race() {
+        let model = Linear { acceleration: 1 };
+
+        for record in [0, 1 << 100, u128::MAX - 1] {
+            assert_eq!(
+                ways_to_win(u128::MAX, record),
+                count_wins(&model, u128::MAX, record)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_models() {
+        assert_eq!(Some(30), model("linear:3").unwrap().speed(10));
+        assert_eq!(Some(7), model("capped:7").unwrap().speed(10));
+        assert_eq!(Some(8), model("charging:5:2").unwrap().speed(2));
+        assert_eq!(Some(111), model("poly:1,10,100").unwrap().speed(1));
+        assert!(model("warp").is_err());
+        assert!(model("capped").is_err());
+        assert!(model("poly:1,x").is_err());
+    }
+
+    #[test]
+    fn winning_interval() {
+        let model = Linear { acceleration: 1 };
+
+        assert_eq!(Some(2..=5), winning_holds(&model, 7, 9));
+        assert_eq!(Some(11..=19), winning_holds(&model, 30, 200));
+        assert_eq!(None, winning_holds(&model, 30, 225));
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use aoc::json;
+
+use crate::model::{self, BoatModel};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceReport {
+    pub time: u128,
+    pub record: u128,
+    // hold times that beat the record
+    pub wins: Option<RangeInclusive<u128>>,
+    pub ways: u128,
+    // the shortest hold reaching the longest distance
+    pub best_hold: u128,
+    // None when it does not fit in a u128
+    pub best_distance: Option<u128>,
+}
+
+impl RaceReport {
+    // How far the best hold beats the record by, None when it does not or
+    // the distance overflows.
+    pub fn margin(&self) -> Option<u128> {
+        self.best_distance?
+            .checked_sub(self.record)
+            .filter(|&m| m > 0)
+    }
+
+    // The lowest record no hold time can beat, None when a u128 record
+    // always can be.
+    pub fn unbeatable_record(&self) -> Option<u128> {
+        self.best_distance
+    }
+
+    pub fn to_json(&self) -> String {
+        let number = |n: Option<u128>| n.map_or("null".to_string(), |n| n.to_string());
+
+        json::object([
+            ("time", self.time.to_string()),
+            ("record", self.record.to_string()),
+            (
+                "wins",
+                self.wins.as_ref().map_or("null".to_string(), |w| {
+                    json::array([w.start().to_string(), w.end().to_string()])
+                }),
+            ),
+            ("ways", self.ways.to_string()),
+            ("best_hold", self.best_hold.to_string()),
+            ("best_distance", number(self.best_distance)),
+            ("margin", number(self.margin())),
+            ("unbeatable_record", number(self.unbeatable_record())),
+        ])
+    }
+}
+
+pub fn report(model: &dyn BoatModel, time: u128, record: u128) -> RaceReport {
+    let wins = model::winning_holds(model, time, record);
+    let best_hold = model::peak(model, time);
+
+    RaceReport {
+        time,
+        record,
+        ways: model::count_wins(model, time, record),
+        wins,
+        best_hold,
+        best_distance: model.distance(best_hold, time),
+    }
+}
+
+pub struct Reports(pub Vec<RaceReport>);
+
+impl Reports {
+    pub fn to_json(&self) -> String {
+        json::array(self.0.iter().map(RaceReport::to_json))
+    }
+}
+
+impl fmt::Display for Reports {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |n: Option<u128>, none: &str| n.map_or(none.to_string(), |n| n.to_string());
+
+        write!(
+            f,
+            "{:>12} {:>14} {:>17} {:>10} {:>9} {:>14} {:>14} {:>14}",
+            "time",
+            "record",
+            "wins",
+            "ways",
+            "best hold",
+            "best distance",
+            "margin",
+            "unbeatable at"
+        )?;
+        for r in &self.0 {
+            let wins = r
+                .wins
+                .as_ref()
+                .map_or("-".to_string(), |w| format!("{}-{}", w.start(), w.end()));
+            write!(
+                f,
+                "\n{:>12} {:>14} {:>17} {:>10} {:>9} {:>14} {:>14} {:>14}",
+                r.time,
+                r.record,
+                wins,
+                r.ways,
+                r.best_hold,
+                show(r.best_distance, "overflow"),
+                show(r.margin(), "-"),
+                show(r.unbeatable_record(), "never")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Capped, Linear};
+
+    #[test]
+    fn linear_report() {
+        let report = report(&Linear { acceleration: 1 }, 30, 200);
+
+        assert_eq!(
+            RaceReport {
+                time: 30,
+                record: 200,
+                wins: Some(11..=19),
+                ways: 9,
+                best_hold: 15,
+                best_distance: Some(225),
+            },
+            report
+        );
+        assert_eq!(Some(25), report.margin());
+        assert_eq!(Some(225), report.unbeatable_record());
+        assert_eq!(
+            r#"{"time":30,"record":200,"wins":[11,19],"ways":9,"best_hold":15,"best_distance":225,"margin":25,"unbeatable_record":225}"#,
+            report.to_json()
+        );
+    }
+
+    #[test]
+    fn unwinnable_race() {
+        let model = Capped {
+            acceleration: 1,
+            top_speed: 10,
+        };
+        let report = report(&model, 30, 200);
+
+        assert_eq!(None, report.wins);
+        assert_eq!(0, report.ways);
+        assert_eq!((10, Some(200)), (report.best_hold, report.best_distance));
+        assert_eq!(None, report.margin());
+        assert!(report.to_json().contains(r#""wins":null"#));
+    }
+
+    #[test]
+    fn report_table() {
+        let model = Linear { acceleration: 1 };
+        let table = Reports(vec![report(&model, 7, 9), report(&model, 4, 4)]).to_string();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            vec!["7", "9", "2-5", "4", "3", "12", "3", "12"],
+            lines[1].split_whitespace().collect::<Vec<_>>()
+        );
+        // 2 * 2 does not beat 4
+        assert_eq!(
+            vec!["4", "4", "-", "0", "2", "4", "-", "4"],
+            lines[2].split_whitespace().collect::<Vec<_>>()
+        );
+    }
+}
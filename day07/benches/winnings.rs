@@ -0,0 +1,68 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+use day7::hand::Hand;
+use day7::rules::Rules;
+
+const HANDS: usize = 1_000_000;
+
+// Deterministic list of a million hands with bids up to 1000.
+fn list_hands() -> String {
+    let mut seed: u64 = 0x2023_1207;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+    let labels: Vec<char> = "23456789TJQKA".chars().collect();
+
+    let mut s = String::with_capacity(HANDS * 10);
+    for _ in 0..HANDS {
+        for _ in 0..5 {
+            s.push(labels[next() % labels.len()]);
+        }
+        s.push_str(&format!(" {}\n", 1 + next() % 1000));
+    }
+    s
+}
+
+fn bench<T>(name: &str, f: impl Fn() -> T) {
+    const RUNS: u32 = 5;
+    f();
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        black_box(f());
+    }
+    println!("{name}: {:?}/iter", start.elapsed() / RUNS);
+}
+
+// the comparator sort the sort keys replaced
+fn compared_winnings(hands: &[(Hand, u32)], rules: &Rules) -> u64 {
+    let mut hands = hands.to_vec();
+    hands.sort_unstable_by(|(a, _), (b, _)| rules.cmp(a, b));
+    hands
+        .iter()
+        .enumerate()
+        .map(|(rank, &(_, bid))| bid as u64 * (rank + 1) as u64)
+        .sum()
+}
+
+fn main() {
+    let s = list_hands();
+    let rules = Rules::default();
+    let jokers = Rules::jokers();
+    let hands = day7::parse_hands(&s, &rules).unwrap();
+
+    bench("parse_hands 1M", || {
+        day7::parse_hands(black_box(&s), &rules)
+    });
+    bench("winnings 1M", || day7::winnings(black_box(&hands), &rules));
+    bench("winnings with jokers 1M", || {
+        day7::winnings(black_box(&hands), &jokers)
+    });
+    bench("comparator sort 1M", || {
+        compared_winnings(black_box(&hands), &rules)
+    });
+}
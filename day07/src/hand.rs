@@ -0,0 +1,319 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::rules::Rules;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Card(char),
+    Suit(char),
+    // a suited card's label has no suit after it
+    MissingSuit,
+    Size { expected: usize, found: usize },
+    Bid(String),
+    MissingBid(String),
+    Rules(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Card(c) => write!(f, "unknown card label {c:?}"),
+            ParseError::Suit(c) => write!(f, "unknown suit {c:?}"),
+            ParseError::MissingSuit => write!(f, "last card has no suit"),
+            ParseError::Size { expected, found } => {
+                write!(f, "hand has {found} cards instead of {expected}")
+            }
+            ParseError::Bid(bid) => write!(f, "invalid bid {bid:?}"),
+            ParseError::MissingBid(line) => write!(f, "no bid after hand: {line}"),
+            ParseError::Rules(msg) => write!(f, "invalid rules: {msg}"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Card {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+pub const CARDS: [(Card, char); 13] = [
+    (Card::Two, '2'),
+    (Card::Three, '3'),
+    (Card::Four, '4'),
+    (Card::Five, '5'),
+    (Card::Six, '6'),
+    (Card::Seven, '7'),
+    (Card::Eight, '8'),
+    (Card::Nine, '9'),
+    (Card::Ten, 'T'),
+    (Card::Jack, 'J'),
+    (Card::Queen, 'Q'),
+    (Card::King, 'K'),
+    (Card::Ace, 'A'),
+];
+
+impl Card {
+    pub fn label(self) -> char {
+        CARDS[self as usize].1
+    }
+}
+
+impl TryFrom<char> for Card {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Card, ParseError> {
+        CARDS
+            .iter()
+            .find(|&&(_, label)| label == c)
+            .map(|&(card, _)| card)
+            .ok_or(ParseError::Card(c))
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub fn label(self) -> char {
+        match self {
+            Suit::Clubs => 'c',
+            Suit::Diamonds => 'd',
+            Suit::Hearts => 'h',
+            Suit::Spades => 's',
+        }
+    }
+}
+
+impl TryFrom<char> for Suit {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Suit, ParseError> {
+        match c {
+            'c' => Ok(Suit::Clubs),
+            'd' => Ok(Suit::Diamonds),
+            'h' => Ok(Suit::Hearts),
+            's' => Ok(Suit::Spades),
+            _ => Err(ParseError::Suit(c)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    // only dealt when the rules give cards suits
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl HandType {
+    // Type of a hand whose cards fall in groups of `counts` equal labels,
+    // with `jokers` more cards free to join any group. Hands larger than five
+    // cards may hold more than one full group; a group of six or more still
+    // only makes five of a kind, the strongest type.
+    pub fn from_counts(counts: impl IntoIterator<Item = usize>, jokers: usize) -> HandType {
+        let (mut largest, mut second) = (0, 0);
+        for n in counts {
+            if n > largest {
+                (largest, second) = (n, largest);
+            } else if n > second {
+                second = n;
+            }
+        }
+
+        match (largest + jokers, second) {
+            (5.., _) => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, 2..) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2..) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::Flush => "flush",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    // one per card, or none when the rules leave cards unsuited
+    pub suits: Vec<Suit>,
+}
+
+impl Hand {
+    // type under the default rules, see Rules::hand_type
+    pub fn hand_type(&self) -> HandType {
+        let mut counts = [0usize; CARDS.len()];
+        for &card in &self.cards {
+            counts[card as usize] += 1;
+        }
+        HandType::from_counts(counts, 0)
+    }
+}
+
+// the default rules: type first, then card by card from the left; suits
+// only keep hands with the same cards apart, as equality does
+impl Ord for Hand {
+    fn cmp(&self, other: &Hand) -> Ordering {
+        self.hand_type()
+            .cmp(&other.hand_type())
+            .then_with(|| self.cards.cmp(&other.cards))
+            .then_with(|| self.suits.cmp(&other.suits))
+    }
+}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Hand) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Hand, ParseError> {
+        Rules::default().parse_hand(s)
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, card) in self.cards.iter().enumerate() {
+            write!(f, "{card}")?;
+            if let Some(suit) = self.suits.get(i) {
+                write!(f, "{}", suit.label())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(s: &str) -> Hand {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn hand_types() {
+        let types: Vec<HandType> = [
+            "23456", "A23A4", "23432", "TTT98", "23332", "AA8AA", "AAAAA",
+        ]
+        .iter()
+        .map(|h| hand(h).hand_type())
+        .collect();
+
+        assert_eq!(
+            vec![
+                HandType::HighCard,
+                HandType::OnePair,
+                HandType::TwoPair,
+                HandType::ThreeOfAKind,
+                HandType::FullHouse,
+                HandType::FourOfAKind,
+                HandType::FiveOfAKind,
+            ],
+            types
+        );
+    }
+
+    #[test]
+    fn sort_hands() {
+        let mut hands = vec![hand("KK677"), hand("33332"), hand("2AAAA"), hand("KTJJT")];
+        hands.sort();
+
+        assert_eq!(
+            vec![hand("KTJJT"), hand("KK677"), hand("2AAAA"), hand("33332")],
+            hands
+        );
+    }
+
+    #[test]
+    fn suits_break_ties() {
+        let hearts = Hand {
+            cards: vec![Card::Ace; 5],
+            suits: vec![Suit::Hearts; 5],
+        };
+        let spades = Hand {
+            suits: vec![Suit::Spades; 5],
+            ..hearts.clone()
+        };
+
+        assert_ne!(hearts, spades);
+        assert_eq!(Ordering::Less, hearts.cmp(&spades));
+    }
+
+    #[test]
+    fn large_hand_types() {
+        let counts = |c: &[usize]| HandType::from_counts(c.iter().copied(), 0);
+
+        assert_eq!(HandType::FullHouse, counts(&[3, 3, 1]));
+        assert_eq!(HandType::TwoPair, counts(&[2, 2, 2, 1]));
+        assert_eq!(HandType::FourOfAKind, counts(&[4, 3]));
+        assert_eq!(HandType::FiveOfAKind, counts(&[7]));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err(ParseError::Card('X')), "23X45".parse::<Hand>());
+        assert_eq!(
+            Err(ParseError::Size {
+                expected: 5,
+                found: 4
+            }),
+            "2345".parse::<Hand>()
+        );
+        assert_eq!("T55J5", hand("T55J5").to_string());
+        assert_eq!(Ok(Card::Ten), Card::try_from('T'));
+    }
+}
@@ -1,102 +1,92 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
-use std::iter::zip;
 
 use aoc::Config;
 
-#[derive(Debug, Copy, Clone)]
-pub enum Joker {
-    Card(char),
-    None,
-}
-
-pub fn run(config: Config) -> Result<(Option<u32>, Option<u32>), Box<dyn Error>> {
-    let list_hands = fs::read_to_string(config.file_path)?;
+pub mod hand;
+pub mod rules;
+pub mod standings;
 
-    let part_one = total_winnings(&list_hands, Joker::None);
-    let part_two = total_winnings(&list_hands, Joker::Card('J'));
+use hand::{Hand, ParseError};
+use rules::Rules;
 
-    Ok((Some(part_one), Some(part_two)))
-}
+// The rule options of Rules::build apply to both parts; part two adds the
+// `--wildcards` labels, jacks by default.
+pub fn run(config: Config) -> Result<(Option<u64>, Option<u64>), Box<dyn Error>> {
+    let list_hands = fs::read_to_string(&config.file_path)?;
+    let (rules, joker_rules) = rules_for(&config)?;
 
-pub fn total_winnings(list_hands: &str, joker_card: Joker) -> u32 {
-    let mut hands = hands_with_bids(list_hands.lines());
-    hands.sort_unstable_by(|(hand_a, _), (hand_b, _)| {
-        let mut order = hand_strength_by_type(hand_a, joker_card)
-            .cmp(&hand_strength_by_type(hand_b, joker_card));
-        if order == Ordering::Equal {
-            order = hand_order_by_label(zip(hand_a.chars(), hand_b.chars()), joker_card);
-        }
-        order
-    });
-
-    hands
-        .iter()
-        .enumerate()
-        .map(|(rank, (_, bid))| bid * (rank + 1) as u32)
-        .sum()
-}
+    let part_one = winnings(&parse_hands(&list_hands, &rules)?, &rules);
+    let part_two = winnings(&parse_hands(&list_hands, &joker_rules)?, &joker_rules);
 
-fn hands_with_bids<'a>(list: impl Iterator<Item = &'a str>) -> Vec<(&'a str, u32)> {
-    list.flat_map(|s| s.split_once(" "))
-        .map(|(hand, bid)| (hand, bid.parse().expect("valid bid number")))
-        .collect()
+    Ok((Some(part_one), Some(part_two)))
 }
 
-fn hand_strength_by_type(hand: &str, joker_card: Joker) -> u32 {
-    let mut cards_count = HashMap::new();
+// `--standings` ranks every hand under the rules of `--part 1|2` (1 by
+// default), as a table, `--csv` or `--json`
+pub fn run_standings(config: &Config) -> Result<String, Box<dyn Error>> {
+    let list_hands = fs::read_to_string(&config.file_path)?;
+    let (rules, joker_rules) = rules_for(config)?;
+    let rules = match config.value("part").unwrap_or("1") {
+        "1" => rules,
+        "2" => joker_rules,
+        part => return Err(format!("unknown part: {part}").into()),
+    };
 
-    for c in hand.chars() {
-        cards_count
-            .entry(c)
-            .and_modify(|counter| *counter += 1)
-            .or_insert(1);
+    let standings = standings::standings(parse_hands(&list_hands, &rules)?, &rules);
+    if config.flag("csv") {
+        return Ok(standings.to_csv());
+    }
+    if config.flag("json") {
+        return Ok(standings.to_json());
     }
+    Ok(standings.to_string())
+}
 
-    let joker = match joker_card {
-        Joker::Card(label) => cards_count.remove(&label).unwrap_or(0),
-        Joker::None => 0,
-    };
+fn rules_for(config: &Config) -> Result<(Rules, Rules), ParseError> {
+    let rules = Rules::build(config)?;
+    let joker_rules = rules
+        .clone()
+        .with_wildcards(config.value("wildcards").unwrap_or("J"))?;
+    Ok((rules, joker_rules))
+}
 
-    match hand.len() - cards_count.len() {
-        // five of a kind
-        4 | 5 => 1 + hand.len() as u32,
-        // four of a kind or full house
-        2 => cards_count.into_values().max().unwrap() + joker,
-        //  three of a kind or two pair
-        3 => cards_count.into_values().max().unwrap() + joker + 1,
-        diff => diff as u32,
-    }
+pub fn parse_hands(list_hands: &str, rules: &Rules) -> Result<Vec<(Hand, u32)>, ParseError> {
+    list_hands
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let (hand, bid) = line
+                .split_once(' ')
+                .ok_or_else(|| ParseError::MissingBid(line.to_string()))?;
+            let bid = bid.trim();
+            Ok((
+                rules.parse_hand(hand)?,
+                bid.parse().map_err(|_| ParseError::Bid(bid.to_string()))?,
+            ))
+        })
+        .collect()
 }
 
-fn hand_order_by_label(cards: impl Iterator<Item = (char, char)>, joker_card: Joker) -> Ordering {
-    let mut order = Ordering::Equal;
-    for (label_a, label_b) in cards {
-        order = label_strength(&label_a, joker_card).cmp(&label_strength(&label_b, joker_card));
-        if order != Ordering::Equal {
-            break;
-        }
-    }
+// Sum of every bid times the rank of its hand, the weakest ranking 1.
+pub fn winnings(hands: &[(Hand, u32)], rules: &Rules) -> u64 {
+    let mut keyed: Vec<(u128, u32)> = hands
+        .iter()
+        .map(|(hand, bid)| (rules.sort_key(hand), *bid))
+        .collect();
+    // stable, so equal hands rank in the order they were listed, as in
+    // standings
+    keyed.sort_by_key(|&(key, _)| key);
 
-    order
+    keyed
+        .iter()
+        .enumerate()
+        .map(|(rank, &(_, bid))| bid as u64 * (rank + 1) as u64)
+        .sum()
 }
 
-fn label_strength<'a>(label: &'a char, joker_card: Joker) -> u32 {
-    if let Joker::Card(joker_label) = joker_card {
-        if joker_label == *label {
-            return 1;
-        }
-    }
-    match label {
-        'A' => 14,
-        'K' => 13,
-        'Q' => 12,
-        'J' => 11,
-        'T' => 10,
-        strength => strength.to_digit(10).unwrap(),
-    }
+pub fn total_winnings(list_hands: &str, rules: &Rules) -> u64 {
+    winnings(&parse_hands(list_hands, rules).unwrap(), rules)
 }
 
 #[cfg(test)]
@@ -112,7 +102,7 @@ KK677 28
 KTJJT 220
 QQQJA 483";
 
-        assert_eq!(6440, total_winnings(list_hands, Joker::None));
+        assert_eq!(6440, total_winnings(list_hands, &Rules::default()));
     }
 
     #[test]
@@ -124,6 +114,34 @@ KK677 28
 KTJJT 220
 QQQJA 483";
 
-        assert_eq!(5905, total_winnings(list_hands, Joker::Card('J')));
+        assert_eq!(5905, total_winnings(list_hands, &Rules::jokers()));
+    }
+
+    #[test]
+    fn ties_keep_listed_order() {
+        let rules = Rules::default();
+        let hands = parse_hands("AAAAA 100\nAAAAA 1", &rules).unwrap();
+
+        assert_eq!(102, winnings(&hands, &rules));
+        assert_eq!(
+            winnings(&hands, &rules),
+            standings::standings(hands, &rules).total()
+        );
+    }
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(
+            Err(ParseError::Bid("x".to_string())),
+            parse_hands("32T3K x", &Rules::default())
+        );
+        assert_eq!(
+            Err(ParseError::MissingBid("32T3K".to_string())),
+            parse_hands("32T3K", &Rules::default())
+        );
+        assert_eq!(
+            Err(ParseError::Card('1')),
+            parse_hands("12345 1", &Rules::default())
+        );
     }
 }
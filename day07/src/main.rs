@@ -9,6 +9,17 @@ fn main() {
         process::exit(1);
     });
 
+    if config.flag("standings") {
+        match day7::run_standings(&config) {
+            Ok(standings) => println!("{standings}"),
+            Err(e) => {
+                eprintln!("Application error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     match day7::run(config) {
         Ok((part1, part2)) => println!("part 1: {:?}\npart 2: {:?}", part1, part2),
         Err(e) => {
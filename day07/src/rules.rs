@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use aoc::Config;
+
+use crate::hand::{Card, Hand, HandType, ParseError, Suit, CARDS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    // card by card from the left, as dealt
+    InOrder,
+    // card by card from the strongest
+    Sorted,
+    // poker kickers: the largest groups first, stronger before weaker
+    Kickers,
+}
+
+impl FromStr for TieBreak {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<TieBreak, ParseError> {
+        match s {
+            "order" => Ok(TieBreak::InOrder),
+            "sorted" => Ok(TieBreak::Sorted),
+            "kickers" => Ok(TieBreak::Kickers),
+            _ => Err(ParseError::Rules(format!("unknown tie-break: {s}"))),
+        }
+    }
+}
+
+// the most cards whose strengths fit in a sort key
+pub const MAX_HAND_SIZE: usize = 31;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    // cards standing in for whichever card makes the best hand; in order
+    // and sorted tie-breaks they rank below every other card
+    pub wildcards: Vec<Card>,
+    // the cards in play, strongest first
+    pub order: Vec<Card>,
+    pub hand_size: usize,
+    pub tie_break: TieBreak,
+    // every card is followed by its suit, and one-suit hands are flushes
+    pub suited: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            wildcards: vec![],
+            order: CARDS.iter().rev().map(|&(card, _)| card).collect(),
+            hand_size: 5,
+            tie_break: TieBreak::InOrder,
+            suited: false,
+        }
+    }
+}
+
+fn parse_labels(labels: &str) -> Result<Vec<Card>, ParseError> {
+    let mut cards: Vec<Card> = vec![];
+    for c in labels.chars() {
+        let card = Card::try_from(c)?;
+        if cards.contains(&card) {
+            return Err(ParseError::Rules(format!("{c} is listed twice")));
+        }
+        cards.push(card);
+    }
+    Ok(cards)
+}
+
+impl Rules {
+    // the second half of the puzzle: jacks are jokers
+    pub fn jokers() -> Rules {
+        Rules {
+            wildcards: vec![Card::Jack],
+            ..Rules::default()
+        }
+    }
+
+    // Rules without wildcards from `--order` (labels strongest first),
+    // `--hand-size`, `--tie-break order|sorted|kickers` and `--suits`.
+    pub fn build(config: &Config) -> Result<Rules, ParseError> {
+        let mut rules = Rules::default();
+
+        if let Some(order) = config.value("order") {
+            rules.order = parse_labels(order)?;
+            if rules.order.is_empty() {
+                return Err(ParseError::Rules("empty card order".to_string()));
+            }
+        }
+        if let Some(size) = config.value("hand-size") {
+            rules.hand_size = size
+                .parse()
+                .ok()
+                .filter(|&n| (1..=MAX_HAND_SIZE).contains(&n))
+                .ok_or_else(|| ParseError::Rules(format!("invalid hand size: {size}")))?;
+        }
+        if let Some(tie_break) = config.value("tie-break") {
+            rules.tie_break = tie_break.parse()?;
+        }
+        rules.suited = config.flag("suits");
+
+        Ok(rules)
+    }
+
+    pub fn with_wildcards(self, labels: &str) -> Result<Rules, ParseError> {
+        Ok(Rules {
+            wildcards: parse_labels(labels)?,
+            ..self
+        })
+    }
+
+    fn is_wild(&self, card: Card) -> bool {
+        self.wildcards.contains(&card)
+    }
+
+    // 0 for wildcards, then rising with the card's place in `order`
+    pub fn strength(&self, card: Card) -> u8 {
+        if self.is_wild(card) {
+            return 0;
+        }
+        self.rank(card)
+    }
+
+    // strength ignoring wildcards, 0 for cards not in `order`
+    fn rank(&self, card: Card) -> u8 {
+        self.order
+            .iter()
+            .position(|&c| c == card)
+            .map_or(0, |i| (self.order.len() - i) as u8)
+    }
+
+    pub fn parse_hand(&self, s: &str) -> Result<Hand, ParseError> {
+        let mut cards = vec![];
+        let mut suits = vec![];
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            let card = Card::try_from(c)?;
+            if !self.order.contains(&card) && !self.is_wild(card) {
+                return Err(ParseError::Card(c));
+            }
+            cards.push(card);
+            if self.suited {
+                suits.push(Suit::try_from(
+                    chars.next().ok_or(ParseError::MissingSuit)?,
+                )?);
+            }
+        }
+
+        if cards.len() != self.hand_size {
+            return Err(ParseError::Size {
+                expected: self.hand_size,
+                found: cards.len(),
+            });
+        }
+        Ok(Hand { cards, suits })
+    }
+
+    // The hand with every wildcard turned into the card of its largest
+    // group, the stronger one on ties; suits are kept.
+    pub fn best_hand(&self, hand: &Hand) -> Hand {
+        let mut counts = [0usize; CARDS.len()];
+        for &card in hand.cards.iter().filter(|&&c| !self.is_wild(c)) {
+            counts[card as usize] += 1;
+        }
+        let target = hand
+            .cards
+            .iter()
+            .copied()
+            .filter(|&c| !self.is_wild(c))
+            .max_by_key(|&c| (counts[c as usize], self.strength(c)))
+            .or_else(|| self.order.iter().copied().find(|&c| !self.is_wild(c)))
+            .unwrap_or(Card::Ace);
+
+        Hand {
+            cards: hand
+                .cards
+                .iter()
+                .map(|&c| if self.is_wild(c) { target } else { c })
+                .collect(),
+            suits: hand.suits.clone(),
+        }
+    }
+
+    pub fn hand_type(&self, hand: &Hand) -> HandType {
+        // wildcards join the largest group, as in best_hand
+        let mut counts = [0usize; CARDS.len()];
+        let mut wild = 0;
+        for &card in &hand.cards {
+            if self.is_wild(card) {
+                wild += 1;
+            } else {
+                counts[card as usize] += 1;
+            }
+        }
+        let hand_type = HandType::from_counts(counts, wild);
+
+        // wildcards fit any suit
+        let mut suits = hand
+            .cards
+            .iter()
+            .zip(&hand.suits)
+            .filter(|&(&c, _)| !self.is_wild(c))
+            .map(|(_, &suit)| suit);
+        let flush = self.suited && suits.next().is_none_or(|first| suits.all(|s| s == first));
+
+        if flush {
+            hand_type.max(HandType::Flush)
+        } else {
+            hand_type
+        }
+    }
+
+    // Card strengths compared, in order, between hands of the same type.
+    pub fn tie_key(&self, hand: &Hand) -> Vec<u8> {
+        match self.tie_break {
+            TieBreak::InOrder => hand.cards.iter().map(|&c| self.strength(c)).collect(),
+            TieBreak::Sorted => {
+                let mut key: Vec<u8> = hand.cards.iter().map(|&c| self.strength(c)).collect();
+                key.sort_unstable_by(|a, b| b.cmp(a));
+                key
+            }
+            TieBreak::Kickers => {
+                let best = self.best_hand(hand);
+                let mut counts = [0usize; CARDS.len()];
+                for &card in &best.cards {
+                    counts[card as usize] += 1;
+                }
+                // wildcards count as the card they stand in for
+                let mut key: Vec<(usize, u8)> = best
+                    .cards
+                    .iter()
+                    .map(|&c| (counts[c as usize], self.rank(c)))
+                    .collect();
+                key.sort_unstable_by(|a, b| b.cmp(a));
+                key.into_iter().map(|(_, s)| s).collect()
+            }
+        }
+    }
+
+    // Type and tie-break strengths packed into one integer ordering hands
+    // like `cmp`: 4 bits each, the type above the cards.
+    pub fn sort_key(&self, hand: &Hand) -> u128 {
+        let hand_type = self.hand_type(hand) as u128;
+        let pack = |key: u128, strength: u8| key << 4 | strength as u128;
+
+        match self.tie_break {
+            TieBreak::InOrder => hand
+                .cards
+                .iter()
+                .fold(hand_type, |key, &c| pack(key, self.strength(c))),
+            _ => self.tie_key(hand).into_iter().fold(hand_type, pack),
+        }
+    }
+
+    pub fn cmp(&self, a: &Hand, b: &Hand) -> Ordering {
+        self.hand_type(a)
+            .cmp(&self.hand_type(b))
+            .then_with(|| self.tie_key(a).cmp(&self.tie_key(b)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(rules: &Rules, s: &str) -> Hand {
+        rules.parse_hand(s).unwrap()
+    }
+
+    fn rules(args: &[&str]) -> Rules {
+        let args = ["day7", "input.txt"].iter().chain(args);
+        Rules::build(&Config::build(args.map(|s| s.to_string())).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn jokers() {
+        let rules = Rules::jokers();
+        let hand_type = |s| rules.hand_type(&hand(&rules, s));
+
+        assert_eq!(HandType::FourOfAKind, hand_type("KTJJT"));
+        assert_eq!(HandType::FiveOfAKind, hand_type("JJJJJ"));
+        assert_eq!(HandType::FullHouse, hand_type("2J233"));
+        assert_eq!(HandType::OnePair, hand_type("2345J"));
+        assert_eq!("KTTTT", rules.best_hand(&hand(&rules, "KTJJT")).to_string());
+        // the joker is the weakest card when breaking ties
+        assert_eq!(
+            Ordering::Less,
+            rules.cmp(&hand(&rules, "JKKK2"), &hand(&rules, "QQQQ2"))
+        );
+    }
+
+    #[test]
+    fn several_wildcards() {
+        let rules = Rules::default().with_wildcards("J2").unwrap();
+
+        assert_eq!(
+            HandType::ThreeOfAKind,
+            rules.hand_type(&hand(&rules, "2J345"))
+        );
+        assert_eq!("AAAAA", rules.best_hand(&hand(&rules, "J22JJ")).to_string());
+        assert!(Rules::default().with_wildcards("JJ").is_err());
+    }
+
+    #[test]
+    fn custom_order_and_size() {
+        let rules = rules(&["--order", "23456789TQKA", "--hand-size", "7"]);
+
+        // twos are now the strongest card, and jacks are out of play
+        assert_eq!(
+            Ordering::Greater,
+            rules.cmp(&hand(&rules, "2AKQT98"), &hand(&rules, "A2KQT98"))
+        );
+        assert_eq!(HandType::TwoPair, rules.hand_type(&hand(&rules, "AAKKQ23")));
+        // two triples make a full house, above even the strongest triple
+        assert_eq!(
+            HandType::FullHouse,
+            rules.hand_type(&hand(&rules, "AAAKKKQ"))
+        );
+        assert_eq!(
+            Ordering::Greater,
+            rules.cmp(&hand(&rules, "AAAKKKQ"), &hand(&rules, "222KQT9"))
+        );
+        assert_eq!(Err(ParseError::Card('J')), rules.parse_hand("AAKKQ2J"));
+        assert_eq!(
+            Err(ParseError::Size {
+                expected: 7,
+                found: 5
+            }),
+            rules.parse_hand("AAKKQ")
+        );
+    }
+
+    #[test]
+    fn tie_breaks() {
+        let in_order = Rules::default();
+        let sorted = rules(&["--tie-break", "sorted"]);
+        let kickers = rules(&["--tie-break=kickers"]);
+        let cmp = |rules: &Rules, a, b| rules.cmp(&hand(rules, a), &hand(rules, b));
+
+        assert_eq!(Ordering::Less, cmp(&in_order, "2345A", "KQT98"));
+        assert_eq!(Ordering::Greater, cmp(&sorted, "2345A", "KQT98"));
+        assert_eq!(Ordering::Equal, cmp(&sorted, "2345A", "A5432"));
+
+        // pairs count before the cards beside them
+        assert_eq!(Ordering::Less, cmp(&in_order, "2AA34", "KK234"));
+        assert_eq!(Ordering::Greater, cmp(&kickers, "2AA34", "KK234"));
+        assert_eq!(Ordering::Equal, cmp(&kickers, "KK223", "22KK3"));
+        assert!("best".parse::<TieBreak>().is_err());
+    }
+
+    #[test]
+    fn sort_keys_match_cmp() {
+        let mut seed: u64 = 0x2023_1207;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize
+        };
+        let labels: Vec<char> = "AKQJT98".chars().collect();
+
+        for args in [
+            &[][..],
+            &["--tie-break", "sorted"],
+            &["--tie-break", "kickers"],
+        ] {
+            let rules = rules(args).with_wildcards("J9").unwrap();
+            let hands: Vec<Hand> = (0..150)
+                .map(|_| {
+                    let s: String = (0..5).map(|_| labels[next() % labels.len()]).collect();
+                    hand(&rules, &s)
+                })
+                .collect();
+
+            for a in &hands {
+                for b in &hands {
+                    assert_eq!(
+                        rules.cmp(a, b),
+                        rules.sort_key(a).cmp(&rules.sort_key(b)),
+                        "{a} vs {b} with {args:?}"
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            0x6_d7777,
+            Rules::default().sort_key(&hand(&Rules::default(), "A8888"))
+        );
+    }
+
+    #[test]
+    fn flushes() {
+        let rules = rules(&["--suits"]);
+        let hand_type = |rules: &Rules, s| rules.hand_type(&hand(rules, s));
+
+        assert_eq!(HandType::Flush, hand_type(&rules, "2h4h6h8hTh"));
+        assert_eq!(HandType::FullHouse, hand_type(&rules, "2h2d2s3h3h"));
+        assert_eq!(HandType::OnePair, hand_type(&rules, "2h2d4h6h8h"));
+        assert_eq!("2h4h6h8hTh", hand(&rules, "2h4h6h8hTh").to_string());
+
+        let jokers = rules.with_wildcards("J").unwrap();
+        assert_eq!(HandType::Flush, hand_type(&jokers, "Jc4h6h8hTh"));
+        assert_eq!(Err(ParseError::Suit('x')), jokers.parse_hand("Jx4h6h8hTh"));
+        assert_eq!(Err(ParseError::MissingSuit), jokers.parse_hand("Jc4h6h8hT"));
+    }
+}
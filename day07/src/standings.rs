@@ -0,0 +1,166 @@
+use std::fmt;
+
+use aoc::json;
+
+use crate::hand::{Hand, HandType};
+use crate::rules::Rules;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub rank: usize,
+    pub hand: Hand,
+    pub hand_type: HandType,
+    // the hand with its wildcards substituted
+    pub best_hand: Hand,
+    // card strengths compared between hands of the same type
+    pub tie_key: Vec<u8>,
+    pub bid: u32,
+    pub winnings: u64,
+}
+
+pub struct Standings(pub Vec<Standing>);
+
+// Hands from the weakest, ranked 1, to the strongest; equal hands keep the
+// order they were listed in.
+pub fn standings(mut hands: Vec<(Hand, u32)>, rules: &Rules) -> Standings {
+    hands.sort_by_cached_key(|(hand, _)| rules.sort_key(hand));
+
+    Standings(
+        hands
+            .into_iter()
+            .enumerate()
+            .map(|(i, (hand, bid))| Standing {
+                rank: i + 1,
+                hand_type: rules.hand_type(&hand),
+                best_hand: rules.best_hand(&hand),
+                tie_key: rules.tie_key(&hand),
+                hand,
+                bid,
+                winnings: bid as u64 * (i + 1) as u64,
+            })
+            .collect(),
+    )
+}
+
+fn tie_key(key: &[u8]) -> String {
+    key.iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+impl Standings {
+    pub fn total(&self) -> u64 {
+        self.0.iter().map(|s| s.winnings).sum()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("rank,hand,type,best_hand,tie_key,bid,winnings");
+        for s in &self.0 {
+            csv.push_str(&format!(
+                "\n{},{},{},{},{},{},{}",
+                s.rank,
+                s.hand,
+                s.hand_type,
+                s.best_hand,
+                tie_key(&s.tie_key),
+                s.bid,
+                s.winnings
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let standings = self.0.iter().map(|s| {
+            json::object([
+                ("rank", s.rank.to_string()),
+                ("hand", json::string(&s.hand.to_string())),
+                ("type", json::string(&s.hand_type.to_string())),
+                ("best_hand", json::string(&s.best_hand.to_string())),
+                (
+                    "tie_key",
+                    json::array(s.tie_key.iter().map(|k| k.to_string())),
+                ),
+                ("bid", s.bid.to_string()),
+                ("winnings", s.winnings.to_string()),
+            ])
+        });
+
+        json::object([
+            ("standings", json::array(standings)),
+            ("total", self.total().to_string()),
+        ])
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>6} {:<10} {:<16} {:<10} {:<16} {:>6} {:>10}",
+            "rank", "hand", "type", "best hand", "tie key", "bid", "winnings"
+        )?;
+        for s in &self.0 {
+            write!(
+                f,
+                "\n{:>6} {:<10} {:<16} {:<10} {:<16} {:>6} {:>10}",
+                s.rank,
+                s.hand.to_string(),
+                s.hand_type.to_string(),
+                s.best_hand.to_string(),
+                tie_key(&s.tie_key),
+                s.bid,
+                s.winnings
+            )?;
+        }
+        write!(f, "\ntotal winnings: {}", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_hands;
+
+    const HANDS: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn joker_standings() {
+        let rules = Rules::jokers();
+        let standings = standings(parse_hands(HANDS, &rules).unwrap(), &rules);
+
+        let order: Vec<String> = standings.0.iter().map(|s| s.hand.to_string()).collect();
+        assert_eq!(vec!["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"], order);
+        assert_eq!(5905, standings.total());
+
+        let top = &standings.0[4];
+        assert_eq!(
+            (HandType::FourOfAKind, "KTTTT".to_string(), 1100),
+            (top.hand_type, top.best_hand.to_string(), top.winnings)
+        );
+    }
+
+    #[test]
+    fn csv_and_json() {
+        let rules = Rules::default();
+        let standings = standings(parse_hands("KK677 28\nKTJJT 220", &rules).unwrap(), &rules);
+
+        assert_eq!(
+            "\
+rank,hand,type,best_hand,tie_key,bid,winnings
+1,KTJJT,two pair,KTJJT,12-9-10-10-9,220,220
+2,KK677,two pair,KK677,12-12-5-6-6,28,56",
+            standings.to_csv()
+        );
+        assert_eq!(
+            r#"{"standings":[{"rank":1,"hand":"KTJJT","type":"two pair","best_hand":"KTJJT","tie_key":[12,9,10,10,9],"bid":220,"winnings":220},{"rank":2,"hand":"KK677","type":"two pair","best_hand":"KK677","tie_key":[12,12,5,6,6],"bid":28,"winnings":56}],"total":276}"#,
+            standings.to_json()
+        );
+    }
+}